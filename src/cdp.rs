@@ -0,0 +1,72 @@
+use crate::errors::ScrapeError;
+use crate::model::CdpRequest;
+use crate::scraper::Scraper;
+use crate::sessions::execute_in_session;
+use chromiumoxide::types::{Command, Method, MethodId};
+use serde::Serialize;
+use std::env;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RawCommand {
+    #[serde(skip)]
+    method: String,
+    #[serde(flatten)]
+    params: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RawCommand {
+    pub fn new(method: &str, params: Option<serde_json::Value>) -> Result<Self, ScrapeError> {
+        let params = match params {
+            None | Some(serde_json::Value::Null) => serde_json::Map::new(),
+            Some(serde_json::Value::Object(map)) => map,
+            Some(_) => {
+                return Err(ScrapeError::InvalidRequest("params must be a JSON object".to_string()));
+            }
+        };
+        Ok(Self { method: method.to_string(), params })
+    }
+}
+
+impl Method for RawCommand {
+    fn identifier(&self) -> MethodId {
+        self.method.clone().into()
+    }
+}
+
+impl Command for RawCommand {
+    type Response = serde_json::Value;
+}
+
+pub fn cdp_enabled() -> bool {
+    env::var("ENABLE_CDP_ENDPOINT")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+fn is_allowed(method: &str) -> bool {
+    env::var("CDP_ALLOWED_COMMANDS")
+        .map(|list| {
+            list.split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .any(|entry| entry == method || (entry.ends_with(".*") && method.starts_with(&entry[..entry.len() - 1])))
+        })
+        .unwrap_or(false)
+}
+
+pub async fn run_cdp(req: &CdpRequest) -> Result<serde_json::Value, ScrapeError> {
+    if !is_allowed(&req.method) {
+        return Err(ScrapeError::InvalidRequest(format!("CDP command not allowed: {}", req.method)));
+    }
+    let command = RawCommand::new(&req.method, req.params.clone())?;
+
+    if let Some(session_id) = &req.session_id {
+        return execute_in_session(session_id, command).await;
+    }
+
+    let scraper = Scraper::new(true).await?;
+    if let Some(url) = &req.url {
+        scraper.goto(url).await?;
+    }
+    scraper.execute_raw(command).await
+}
@@ -0,0 +1,216 @@
+use crate::errors::ScrapeError;
+use crate::model::ProxyConfig;
+use reqwest::Url;
+use reqwest::header::LOCATION;
+use reqwest::redirect::Policy;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+const MAX_IMAGE_REDIRECTS: usize = 5;
+const PALETTE_SIZE: usize = 5;
+const SAMPLE_SIZE: u32 = 64;
+
+type Bucket = (u64, u64, u64, u64);
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => {
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local())
+        }
+    }
+}
+
+async fn resolve_public(url: &Url) -> Result<SocketAddr, ScrapeError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ScrapeError::ContentExtraction(format!("image url scheme not allowed: {}", url)));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| ScrapeError::ContentExtraction(format!("image url has no host: {}", url)))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.trim_matches(['[', ']']), port))
+        .await
+        .map_err(|e| ScrapeError::ContentExtraction(format!("image host {}: {}", host, e)))?
+        .collect();
+    if addrs.is_empty() || addrs.iter().any(|addr| !is_public(addr.ip())) {
+        return Err(ScrapeError::ContentExtraction(format!("image host not allowed: {}", host)));
+    }
+    Ok(addrs[0])
+}
+
+fn image_client(url: &Url, addr: SocketAddr, proxy: Option<&ProxyConfig>) -> Result<reqwest::Client, ScrapeError> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .redirect(Policy::none());
+    match proxy {
+        Some(proxy) if proxy.scheme().starts_with("socks") => {
+            return Err(ScrapeError::ContentExtraction(format!(
+                "image download through {} proxies is not supported",
+                proxy.scheme()
+            )));
+        }
+        Some(proxy) => {
+            let mut upstream = reqwest::Proxy::all(proxy.server())
+                .map_err(|e| ScrapeError::ContentExtraction(format!("image proxy: {}", e)))?;
+            if let Some((username, password)) = proxy.credentials() {
+                upstream = upstream.basic_auth(&username, &password);
+            }
+            builder = builder.proxy(upstream);
+        }
+        None => {
+            builder = builder.no_proxy();
+            if let Some(host) = url.host_str() {
+                builder = builder.resolve(host, addr);
+            }
+        }
+    }
+    builder
+        .build()
+        .map_err(|e| ScrapeError::ContentExtraction(format!("image client: {}", e)))
+}
+
+pub async fn fetch_image(url: &str, proxy: Option<&ProxyConfig>) -> Result<Vec<u8>, ScrapeError> {
+    let mut url = Url::parse(url).map_err(|e| ScrapeError::ContentExtraction(format!("image url {}: {}", url, e)))?;
+    let mut redirects = 0;
+    let mut response = loop {
+        let addr = resolve_public(&url).await?;
+        let response = image_client(&url, addr, proxy)?
+            .get(url.clone())
+            .send()
+            .await
+            .map_err(|e| ScrapeError::ContentExtraction(format!("image download: {}", e)))?;
+        if !response.status().is_redirection() {
+            break response
+                .error_for_status()
+                .map_err(|e| ScrapeError::ContentExtraction(format!("image download: {}", e)))?;
+        }
+        redirects += 1;
+        if redirects > MAX_IMAGE_REDIRECTS {
+            return Err(ScrapeError::ContentExtraction("image redirected too many times".to_string()));
+        }
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| ScrapeError::ContentExtraction("image redirect without location".to_string()))?;
+        url = url
+            .join(location)
+            .map_err(|e| ScrapeError::ContentExtraction(format!("image redirect {}: {}", location, e)))?;
+    };
+
+    if response.content_length().is_some_and(|len| len as usize > MAX_IMAGE_BYTES) {
+        return Err(ScrapeError::ContentExtraction("image exceeds size limit".to_string()));
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| ScrapeError::ContentExtraction(format!("image download: {}", e)))?
+    {
+        if bytes.len() + chunk.len() > MAX_IMAGE_BYTES {
+            return Err(ScrapeError::ContentExtraction("image exceeds size limit".to_string()));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+pub fn palette(bytes: &[u8]) -> Result<Vec<String>, ScrapeError> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| ScrapeError::ContentExtraction(format!("image decode: {}", e)))?
+        .thumbnail(SAMPLE_SIZE, SAMPLE_SIZE)
+        .to_rgba8();
+
+    let mut buckets: HashMap<(u8, u8, u8), Bucket> = HashMap::new();
+    for pixel in image.pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+        let entry = buckets.entry((r >> 5, g >> 5, b >> 5)).or_default();
+        entry.0 += r as u64;
+        entry.1 += g as u64;
+        entry.2 += b as u64;
+        entry.3 += 1;
+    }
+
+    let mut ranked: Vec<Bucket> = buckets.into_values().collect();
+    ranked.sort_by_key(|bucket| std::cmp::Reverse(bucket.3));
+    Ok(ranked
+        .into_iter()
+        .take(PALETTE_SIZE)
+        .map(|(r, g, b, count)| format!("#{:02x}{:02x}{:02x}", r / count, g / count, b / count))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    fn solid_png(color: [u8; 3]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbImage::from_pixel(32, 32, Rgb(color))
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn palette_of_a_solid_image_is_that_colour() {
+        assert_eq!(palette(&solid_png([0x12, 0x80, 0xee])).unwrap(), vec!["#1280ee".to_string()]);
+    }
+
+    #[test]
+    fn palette_rejects_bytes_that_are_not_an_image() {
+        assert!(palette(b"not an image").is_err());
+    }
+
+    #[test]
+    fn internal_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{} should be rejected", ip);
+        }
+        assert!(is_public("93.184.216.34".parse().unwrap()));
+        assert!(is_public("2606:2800:220:1:248:1893:25c8:1946".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn image_urls_on_internal_hosts_are_refused() {
+        for url in ["http://127.0.0.1/logo.png", "http://[::1]/logo.png", "http://localhost/logo.png"] {
+            assert!(fetch_image(url, None).await.is_err(), "{} should be refused", url);
+        }
+    }
+}
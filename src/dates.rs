@@ -0,0 +1,57 @@
+use crate::model::{DateCandidate, DateData};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+
+fn parse_relative(raw: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let lower = raw.to_lowercase();
+    match lower.trim() {
+        "today" | "just now" => return Some(now),
+        "yesterday" => return Some(now - Duration::days(1)),
+        _ => {}
+    }
+
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    if words.len() != 3 || words[2] != "ago" {
+        return None;
+    }
+    let amount: i64 = match words[0] {
+        "a" | "an" => 1,
+        n => n.parse().ok()?,
+    };
+    let unit = words[1].trim_end_matches('s');
+    let delta = match unit {
+        "second" | "sec" => Duration::seconds(amount),
+        "minute" | "min" => Duration::minutes(amount),
+        "hour" | "hr" => Duration::hours(amount),
+        "day" => Duration::days(amount),
+        "week" => Duration::weeks(amount),
+        "month" => Duration::days(amount * 30),
+        "year" => Duration::days(amount * 365),
+        _ => return None,
+    };
+    Some(now - delta)
+}
+
+pub fn normalize_dates(candidates: Vec<DateCandidate>) -> Vec<DateData> {
+    let now = Utc::now();
+    let mut seen = HashSet::new();
+    let mut dates = Vec::new();
+
+    for candidate in candidates {
+        let raw = candidate.raw.trim();
+        let parsed = parse_relative(raw, now).or_else(|| dateparser::parse(raw).ok());
+        let Some(parsed) = parsed else {
+            continue;
+        };
+        let normalized = parsed.to_rfc3339();
+        if seen.insert(normalized.clone()) {
+            dates.push(DateData {
+                raw: raw.to_string(),
+                normalized,
+                source: candidate.source,
+            });
+        }
+    }
+
+    dates
+}
@@ -9,6 +9,30 @@ pub enum ScrapeError {
     LoginFailed(String),
     TwoFactorAuthRequired,
     ContentExtraction(String),
+    InvalidCertificate(String),
+    BrowserLimitReached(usize),
+    InvalidRequest(String),
+}
+
+impl ScrapeError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScrapeError::BrowserLaunch(_) => "browser_launch",
+            ScrapeError::Navigation(_) => "navigation",
+            ScrapeError::PageCreation(_) => "page_creation",
+            ScrapeError::EvaluationFailed(_) => "evaluation_failed",
+            ScrapeError::LoginFailed(_) => "login_failed",
+            ScrapeError::TwoFactorAuthRequired => "two_factor_auth_required",
+            ScrapeError::ContentExtraction(_) => "content_extraction",
+            ScrapeError::InvalidCertificate(_) => "invalid_certificate",
+            ScrapeError::BrowserLimitReached(_) => "browser_limit_reached",
+            ScrapeError::InvalidRequest(_) => "invalid_request",
+        }
+    }
+
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ScrapeError::Navigation(_))
+    }
 }
 
 impl fmt::Display for ScrapeError {
@@ -21,6 +45,9 @@ impl fmt::Display for ScrapeError {
             ScrapeError::LoginFailed(e) => write!(f, "Automatic login failed: {}", e),
             ScrapeError::TwoFactorAuthRequired => write!(f, "2FA is required, cannot proceed automatically"),
             ScrapeError::ContentExtraction(e) => write!(f, "Failed to extract content: {}", e),
+            ScrapeError::InvalidRequest(e) => write!(f, "Invalid request: {}", e),
+            ScrapeError::BrowserLimitReached(max) => write!(f, "Browser limit reached ({} active), try again later", max),
+            ScrapeError::InvalidCertificate(e) => write!(f, "Invalid TLS certificate (set ignore_https_errors to bypass): {}", e),
         }
     }
 }
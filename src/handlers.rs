@@ -1,43 +1,531 @@
-use actix_web::{HttpResponse, web, Responder};
-use crate::model::{ScrapeRequest, ScrapeResponse};
-use crate::scraper::do_scrape;
+use actix_web::{HttpRequest, HttpResponse, HttpResponseBuilder, web, Responder, http::header};
+use futures::StreamExt;
+use futures::channel::mpsc;
+use crate::cdp::{cdp_enabled, run_cdp};
+use crate::errors::ScrapeError;
+use crate::jobs;
+use crate::metrics;
+use crate::progress::{emit, sse_frame, with_progress};
+use crate::model::{
+    CdpRequest, CrawlOptions, MultiLoginRequest, RenderResponse, ScrapeGetQuery, ScrapeQuery,
+    ScrapeRequest, ScrapeResponse, ScrapedData,
+};
+use crate::scraper::{do_crawl, do_multi_login, do_render, do_scrape, do_scrape_batch};
+use crate::sessions::close_session;
+use crate::sitemap;
+use crate::webhook;
 
 pub async fn health() -> impl Responder {
     HttpResponse::Ok().body("OK")
 }
 
-pub async fn scrape(req: web::Json<ScrapeRequest>) -> impl Responder {
+pub async fn export_metrics() -> impl Responder {
+    match metrics::render() {
+        Ok(body) => HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4; charset=utf-8")
+            .body(body),
+        Err(e) => HttpResponse::InternalServerError().body(e),
+    }
+}
+
+pub async fn login_multi(req: web::Json<MultiLoginRequest>) -> impl Responder {
+    match do_multi_login(&req).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e @ ScrapeError::BrowserLimitReached(_)) => HttpResponse::ServiceUnavailable().body(e.to_string()),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+pub async fn cdp(req: web::Json<CdpRequest>) -> impl Responder {
+    if !cdp_enabled() {
+        return HttpResponse::NotFound().finish();
+    }
+    match run_cdp(&req).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e @ ScrapeError::InvalidRequest(_)) => HttpResponse::BadRequest().body(e.to_string()),
+        Err(e @ ScrapeError::BrowserLimitReached(_)) => HttpResponse::ServiceUnavailable().body(e.to_string()),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+pub async fn render(req: web::Json<ScrapeRequest>) -> impl Responder {
     let url = req.url.clone();
-    let login = req.login.clone();
-    
-    match do_scrape(&url, login).await {
-        Ok(data) => HttpResponse::Ok().json(ScrapeResponse {
-            title: data.title,
-            description: data.description,
-            url: url.clone(),
-            text: data.text,
-            images: data.images,
-            links: data.links,
-            success: true,
-            error: None,
-            login_attempted: data.login_attempted,
-            login_success: data.login_success,
-            platform_detected: data.platform_detected,
-            requires_2fa: data.requires_2fa,
-        }),
-        Err(e) => HttpResponse::InternalServerError().json(ScrapeResponse {
-            title: None,
-            description: None,
-            url: url.clone(),
-            text: None,
-            images: Vec::new(),
-            links: Vec::new(),
-            success: false,
-            error: Some(e.to_string()),
-            login_attempted: false,
-            login_success: None,
-            platform_detected: None,
-            requires_2fa: None,
+    match do_render(&req).await {
+        Ok(html) => HttpResponse::Ok().json(RenderResponse { url, success: true, html: Some(html), error: None }),
+        Err(e) => error_status(&e).json(RenderResponse { url, success: false, html: None, error: Some(e.to_string()) }),
+    }
+}
+
+pub async fn create_job(req: web::Json<ScrapeRequest>) -> impl Responder {
+    let mut req = req.into_inner();
+    if let Some(setting) = req.from_sitemap.take() {
+        match sitemap_urls(&req, &setting).await {
+            Ok(urls) => {
+                req.urls = Some(urls);
+                req.crawl = None;
+            }
+            Err(e) => return error_status(&e).body(e.to_string()),
+        }
+    }
+    if req.url.trim().is_empty() && req.urls.is_none() {
+        return HttpResponse::BadRequest().body("Either url or urls is required");
+    }
+    let urls = req.urls.clone().unwrap_or_else(|| vec![req.url.clone()]);
+    if urls.len() > MAX_BATCH_URLS {
+        return HttpResponse::BadRequest().body(format!("At most {} urls per request", MAX_BATCH_URLS));
+    }
+    let job_id = jobs::create(urls.len());
+
+    let id = job_id.clone();
+    actix_web::rt::spawn(async move {
+        jobs::start(&id);
+        let record = |url: &str, result: Result<ScrapedData, ScrapeError>| {
+            let (response, success) = match result {
+                Ok(data) => (success_response(url.to_string(), data), true),
+                Err(e) => (error_response(url.to_string(), &e), false),
+            };
+            jobs::record(&id, aliased(&req, response), success);
+        };
+        let outcome = match &req.crawl {
+            Some(options) => do_crawl(&req, options, record).await,
+            None => do_scrape_batch(&req, &urls, record).await,
+        };
+        jobs::finish(&id, outcome.err().map(|e| e.to_string()));
+    });
+
+    HttpResponse::Accepted().json(serde_json::json!({ "job_id": job_id }))
+}
+
+pub async fn get_job(path: web::Path<String>) -> impl Responder {
+    match jobs::get(&path) {
+        Some(job) => HttpResponse::Ok().json(job),
+        None => HttpResponse::NotFound().body("Job not found"),
+    }
+}
+
+pub async fn delete_session(path: web::Path<String>) -> impl Responder {
+    if close_session(&path).await {
+        HttpResponse::NoContent().finish()
+    } else {
+        HttpResponse::NotFound().body("Session not found")
+    }
+}
+
+fn wants_csv(http_req: &HttpRequest, format: Option<&str>) -> bool {
+    if let Some(format) = format {
+        return format.eq_ignore_ascii_case("csv");
+    }
+    http_req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains("text/csv"))
+        .unwrap_or(false)
+}
+
+fn to_csv(data: &ScrapedData, collection: &str) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    match collection {
+        "links" => {
+            writer.write_record(["href", "text", "is_internal"]).map_err(|e| e.to_string())?;
+            for link in &data.links {
+                writer
+                    .write_record([link.href.as_str(), link.text.as_str(), if link.is_internal { "true" } else { "false" }])
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        "images" => {
+            writer.write_record(["src", "alt"]).map_err(|e| e.to_string())?;
+            for image in &data.images {
+                writer
+                    .write_record([image.src.as_str(), image.alt.as_str()])
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        other => return Err(format!("Unsupported csv_collection: {}", other)),
+    }
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+pub async fn scrape(
+    http_req: HttpRequest,
+    query: web::Query<ScrapeQuery>,
+    req: web::Json<ScrapeRequest>,
+) -> impl Responder {
+    let csv = wants_csv(&http_req, query.format.as_deref());
+    run_scrape(&req, csv).await
+}
+
+pub async fn scrape_get(
+    http_req: HttpRequest,
+    query: web::Query<ScrapeGetQuery>,
+) -> impl Responder {
+    let query = query.into_inner();
+    let csv = wants_csv(&http_req, query.format.as_deref());
+    let req = ScrapeRequest::from(query);
+    run_scrape(&req, csv).await
+}
+
+const ENVELOPE_META_FIELDS: &[&str] = &[
+    "url",
+    "success",
+    "redirect_chain",
+    "extraction_warnings",
+    "session_refreshed",
+    "extraction_retried",
+];
+
+fn aliased(req: &ScrapeRequest, response: ScrapeResponse) -> serde_json::Value {
+    let value = serde_json::to_value(&response).unwrap_or(serde_json::Value::Null);
+    if req.envelope {
+        return enveloped(req, value);
+    }
+    rename_fields(req, value)
+}
+
+fn enveloped(req: &ScrapeRequest, value: serde_json::Value) -> serde_json::Value {
+    let serde_json::Value::Object(mut data) = value else {
+        return value;
+    };
+    let mut meta = serde_json::Map::new();
+    for key in ENVELOPE_META_FIELDS {
+        if let Some(field) = data.remove(*key) {
+            meta.insert(key.to_string(), field);
+        }
+    }
+    let final_url = meta
+        .get("redirect_chain")
+        .and_then(|chain| chain.as_array())
+        .and_then(|chain| chain.last())
+        .or_else(|| meta.get("url"))
+        .cloned()
+        .unwrap_or(serde_json::Value::Null);
+    meta.insert("final_url".to_string(), final_url);
+
+    let errors: Vec<serde_json::Value> = match data.remove("error") {
+        Some(serde_json::Value::String(detail)) => vec![serde_json::json!({ "detail": detail })],
+        _ => Vec::new(),
+    };
+
+    serde_json::json!({
+        "data": rename_fields(req, data.into()),
+        "meta": meta,
+        "errors": errors,
+    })
+}
+
+fn rename_fields(req: &ScrapeRequest, value: serde_json::Value) -> serde_json::Value {
+    let Some(aliases) = req.field_aliases.as_ref().filter(|a| !a.is_empty()) else {
+        return value;
+    };
+    match value {
+        serde_json::Value::Object(fields) => fields
+            .into_iter()
+            .map(|(key, value)| (aliases.get(&key).cloned().unwrap_or(key), value))
+            .collect::<serde_json::Map<String, serde_json::Value>>()
+            .into(),
+        other => other,
+    }
+}
+
+pub async fn scrape_live(req: web::Json<ScrapeRequest>) -> impl Responder {
+    let req = req.into_inner();
+    let (sender, receiver) = mpsc::unbounded::<String>();
+    let result_sender = sender.clone();
+
+    actix_web::rt::spawn(async move {
+        let url = req.url.clone();
+        let (event, response) = with_progress(sender, async {
+            emit("status", format!("Scrape started for {}", url));
+            match do_scrape(&req).await {
+                Ok(data) => ("result", success_response(url.clone(), data)),
+                Err(e) => {
+                    emit("status", format!("Scrape failed: {}", e));
+                    ("error", error_response(url.clone(), &e))
+                }
+            }
         })
+        .await;
+        let body = aliased(&req, response).to_string();
+        let _ = result_sender.unbounded_send(sse_frame(event, &body));
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(receiver.map(|frame| Ok::<_, actix_web::Error>(web::Bytes::from(frame))))
+}
+
+fn with_aliases(
+    mut builder: HttpResponseBuilder,
+    req: &ScrapeRequest,
+    response: ScrapeResponse,
+) -> HttpResponse {
+    builder.json(aliased(req, response))
+}
+
+fn error_status(e: &ScrapeError) -> HttpResponseBuilder {
+    match e {
+        ScrapeError::BrowserLimitReached(_) => HttpResponse::ServiceUnavailable(),
+        ScrapeError::InvalidRequest(_) => HttpResponse::BadRequest(),
+        _ => HttpResponse::InternalServerError(),
+    }
+}
+
+const MAX_BATCH_URLS: usize = 100;
+
+async fn run_batch(req: &ScrapeRequest, urls: &[String], csv: bool) -> HttpResponse {
+    if csv {
+        return HttpResponse::BadRequest().body("CSV output is not supported for batch requests");
+    }
+    if urls.len() > MAX_BATCH_URLS {
+        return HttpResponse::BadRequest().body(format!("At most {} urls per request", MAX_BATCH_URLS));
+    }
+
+    let mut responses = Vec::with_capacity(urls.len());
+    let outcome = do_scrape_batch(req, urls, |url, result| {
+        let response = match result {
+            Ok(data) => success_response(url.to_string(), data),
+            Err(e) => error_response(url.to_string(), &e),
+        };
+        responses.push(aliased(req, response));
+    })
+    .await;
+
+    match outcome {
+        Ok(()) => HttpResponse::Ok().json(responses),
+        Err(e) => with_aliases(error_status(&e), req, error_response(req.url.clone(), &e)),
+    }
+}
+
+fn run_with_callback(req: &ScrapeRequest, callback_url: &str) -> HttpResponse {
+    if !webhook::is_valid_callback(callback_url) {
+        return HttpResponse::BadRequest().body(format!("Invalid callback_url: {}", callback_url));
+    }
+    let job_id = jobs::create(1);
+
+    let (id, req, callback_url) = (job_id.clone(), req.clone(), callback_url.to_string());
+    actix_web::rt::spawn(async move {
+        jobs::start(&id);
+        let url = req.url.clone();
+        let result = do_scrape(&req).await;
+        let (response, success) = match result {
+            Ok(data) => (success_response(url, data), true),
+            Err(e) => (error_response(url, &e), false),
+        };
+        let mut payload = aliased(&req, response);
+        jobs::record(&id, payload.clone(), success);
+        jobs::finish(&id, None);
+
+        if let Some(object) = payload.as_object_mut() {
+            object.insert("job_id".to_string(), serde_json::Value::String(id.clone()));
+        }
+        if !webhook::deliver(&callback_url, &payload).await {
+            tracing::warn!("Giving up on callback for job {}", id);
+        }
+    });
+
+    HttpResponse::Accepted().json(serde_json::json!({ "job_id": job_id }))
+}
+
+async fn run_crawl(req: &ScrapeRequest, options: &CrawlOptions, csv: bool) -> HttpResponse {
+    if csv {
+        return HttpResponse::BadRequest().body("CSV output is not supported for crawl requests");
     }
-}
\ No newline at end of file
+
+    let mut responses = Vec::new();
+    let outcome = do_crawl(req, options, |url, result| {
+        let response = match result {
+            Ok(data) => success_response(url.to_string(), data),
+            Err(e) => error_response(url.to_string(), &e),
+        };
+        responses.push(aliased(req, response));
+    })
+    .await;
+
+    match outcome {
+        Ok(()) => HttpResponse::Ok().json(responses),
+        Err(e) => with_aliases(error_status(&e), req, error_response(req.url.clone(), &e)),
+    }
+}
+
+async fn sitemap_urls(req: &ScrapeRequest, setting: &str) -> Result<Vec<String>, ScrapeError> {
+    let limit = req
+        .crawl
+        .as_ref()
+        .and_then(|crawl| crawl.max_pages)
+        .unwrap_or(MAX_BATCH_URLS)
+        .min(MAX_BATCH_URLS);
+    let url = sitemap::sitemap_url(setting, &req.url)?;
+    emit("status", format!("Reading sitemap {}", url));
+    sitemap::collect_urls(&url, limit).await
+}
+
+async fn run_scrape(req: &ScrapeRequest, csv: bool) -> HttpResponse {
+    if let Some(setting) = &req.from_sitemap {
+        return match sitemap_urls(req, setting).await {
+            Ok(urls) => run_batch(req, &urls, csv).await,
+            Err(e) => with_aliases(error_status(&e), req, error_response(req.url.clone(), &e)),
+        };
+    }
+    if req.url.trim().is_empty() && req.urls.is_none() {
+        return HttpResponse::BadRequest().body("Either url or urls is required");
+    }
+    if let Some(options) = &req.crawl {
+        return run_crawl(req, options, csv).await;
+    }
+    if let Some(urls) = &req.urls {
+        return run_batch(req, urls, csv).await;
+    }
+    if let Some(callback_url) = &req.callback_url {
+        return run_with_callback(req, callback_url);
+    }
+    let url = req.url.clone();
+
+    let result = do_scrape(req).await;
+
+    let unchanged = |data: &ScrapedData| {
+        req.if_none_match
+            .as_deref()
+            .is_some_and(|tag| tag.trim_matches('"') == data.content_hash)
+    };
+    match result {
+        Ok(data) if unchanged(&data) => {
+            HttpResponse::NotModified()
+                .insert_header((header::ETAG, format!("\"{}\"", data.content_hash)))
+                .finish()
+        }
+        Ok(data) if csv => {
+            let collection = req.csv_collection.as_deref().unwrap_or("links");
+            match to_csv(&data, collection) {
+                Ok(body) => HttpResponse::Ok()
+                    .content_type("text/csv; charset=utf-8")
+                    .body(body),
+                Err(e) => HttpResponse::BadRequest().body(e),
+            }
+        }
+        Ok(data) => with_aliases(HttpResponse::Ok(), req, success_response(url, data)),
+        Err(e) => with_aliases(error_status(&e), req, error_response(url, &e)),
+    }
+}
+
+fn success_response(url: String, data: ScrapedData) -> ScrapeResponse {
+    ScrapeResponse {
+        title: data.title,
+        description: data.description,
+        url,
+        text: data.text,
+        images: data.images,
+        links: data.links,
+        success: true,
+        error: None,
+        login_attempted: data.login_attempted,
+        login_success: data.login_success,
+        platform_detected: data.platform_detected,
+        requires_2fa: data.requires_2fa,
+        landmarks: data.landmarks,
+        hidden_text: data.hidden_text,
+        xhr_responses: data.xhr_responses,
+        matched_count: data.matched_count,
+        selector_results: data.selector_results,
+        text_direction: data.text_direction,
+        script: data.script,
+        extraction_warnings: data.extraction_warnings,
+        session_refreshed: data.session_refreshed,
+        form_state: data.form_state,
+        cookies_set: data.cookies_set,
+        noscript_content: data.noscript_content,
+        faqs: data.faqs,
+        redirect_chain: data.redirect_chain,
+        dates: data.dates,
+        framework: data.framework,
+        sections: data.sections,
+        screenshot: data.screenshot,
+        blocked_requests: data.blocked_requests,
+        broken_anchors: data.broken_anchors,
+        new_links: data.new_links,
+        computed_styles: data.computed_styles,
+        products: data.products,
+        warmup_performed: data.warmup_performed,
+        cmp: data.cmp,
+        login_frame: data.login_frame,
+        title_history: data.title_history,
+        text_stats: data.text_stats,
+        followed_tab: data.followed_tab,
+        extraction_retried: data.extraction_retried,
+        dominant_color: data.dominant_color,
+        palette: data.palette,
+        proxy_used: data.proxy_used,
+        pdf: data.pdf,
+        metadata: data.metadata,
+        structured_data: data.structured_data,
+        extracted: data.extracted,
+        tables: data.tables,
+        cookies: data.cookies,
+        script_result: data.script_result,
+        language: data.language,
+        language_confidence: data.language_confidence,
+        content_hash: data.content_hash,
+    }
+}
+
+fn error_response(url: String, e: &ScrapeError) -> ScrapeResponse {
+    ScrapeResponse {
+        title: None,
+        description: None,
+        url,
+        text: None,
+        images: Vec::new(),
+        links: Vec::new(),
+        success: false,
+        error: Some(e.to_string()),
+        login_attempted: false,
+        login_success: None,
+        platform_detected: None,
+        requires_2fa: None,
+        landmarks: None,
+        hidden_text: None,
+        xhr_responses: None,
+        matched_count: None,
+        selector_results: None,
+        text_direction: None,
+        script: None,
+        extraction_warnings: Vec::new(),
+        session_refreshed: false,
+        form_state: None,
+        cookies_set: None,
+        noscript_content: None,
+        faqs: None,
+        redirect_chain: Vec::new(),
+        dates: None,
+        framework: None,
+        sections: None,
+        screenshot: None,
+        blocked_requests: None,
+        broken_anchors: None,
+        new_links: None,
+        computed_styles: None,
+        products: None,
+        warmup_performed: false,
+        cmp: None,
+        login_frame: None,
+        title_history: None,
+        text_stats: None,
+        followed_tab: None,
+        extraction_retried: false,
+        dominant_color: None,
+        palette: None,
+        proxy_used: None,
+        pdf: None,
+        metadata: Default::default(),
+        structured_data: Vec::new(),
+        extracted: Default::default(),
+        tables: Vec::new(),
+        cookies: None,
+        script_result: None,
+        language: None,
+        language_confidence: None,
+        content_hash: String::new(),
+    }
+}
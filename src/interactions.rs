@@ -0,0 +1,213 @@
+use chromiumoxide::Page;
+use chromiumoxide::cdp::js_protocol::runtime::{EvaluateParams, ExecutionContextId};
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info};
+
+pub async fn eval_in<T: DeserializeOwned>(
+    page: &Page,
+    context: Option<ExecutionContextId>,
+    script: impl Into<String>,
+) -> Result<T, Box<dyn Error + Send + Sync>> {
+    let mut params = EvaluateParams::builder()
+        .expression(script)
+        .await_promise(true)
+        .return_by_value(true);
+    if let Some(context) = context {
+        params = params.context_id(context);
+    }
+    Ok(page.evaluate_expression(params.build()?).await?.into_value()?)
+}
+
+pub async fn wait_for_any_element(
+    page: &Page,
+    context: Option<ExecutionContextId>,
+    selectors: &[String],
+    timeout_ms: u64,
+) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
+    let check_interval_ms = 300;
+    let mut elapsed = 0;
+
+    debug!("Searching for: {:?}", selectors);
+
+    while elapsed < timeout_ms {
+        for selector in selectors {
+            let is_visible: bool = eval_in(page, context, format!(
+                r#"
+                (() => {{
+                    try {{
+                        const el = document.querySelector({});
+                        if (!el) return false;
+                        
+                        const style = window.getComputedStyle(el);
+                        const rect = el.getBoundingClientRect();
+                        
+                        return el.offsetParent !== null &&
+                               style.visibility !== 'hidden' && 
+                               style.display !== 'none' && 
+                               parseFloat(style.opacity) > 0 &&
+                               rect.width > 0 && 
+                               rect.height > 0;
+                    }} catch(e) {{
+                        return false;
+                    }}
+                }})()
+                "#,
+                serde_json::to_string(selector).unwrap_or_default()
+            )).await.unwrap_or(false);
+
+            if is_visible {
+                info!("Found element: {}", selector);
+                return Ok(Some(selector.clone()));
+            }
+        }
+        
+        sleep(Duration::from_millis(check_interval_ms)).await;
+        elapsed += check_interval_ms;
+    }
+
+    Ok(None)
+}
+
+pub async fn type_into_field(
+    page: &Page,
+    selector: &str,
+    text: &str,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    type_into_field_in(page, None, selector, text).await
+}
+
+pub async fn type_into_field_in(
+    page: &Page,
+    context: Option<ExecutionContextId>,
+    selector: &str,
+    text: &str,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    info!("Typing into field: {}", selector);
+    
+    let base_delay = 60;
+    let variance = 40;
+    
+    let result: bool = eval_in(page, context, format!(
+        r#"
+        (async () => {{
+            try {{
+                const field = document.querySelector({});
+                if (!field || field.offsetParent === null) return false;
+                
+                field.scrollIntoView({{ behavior: 'smooth', block: 'center' }});
+                await new Promise(r => setTimeout(r, 400));
+                
+                field.focus();
+                field.click();
+                await new Promise(r => setTimeout(r, 150));
+                
+                field.value = '';
+                field.dispatchEvent(new Event('focus', {{ bubbles: true }}));
+                
+                const text = {};
+                
+                for (let i = 0; i < text.length; i++) {{
+                    const char = text.charAt(i);
+                    const delay = {} + Math.floor(Math.random() * {});
+                    
+                    await new Promise(r => setTimeout(r, delay));
+                    
+                    field.value += char;
+                    
+                    field.dispatchEvent(new InputEvent('input', {{ 
+                        data: char,
+                        inputType: 'insertText',
+                        bubbles: true
+                    }}));
+                    
+                    field.dispatchEvent(new KeyboardEvent('keydown', {{ 
+                        key: char,
+                        bubbles: true
+                    }}));
+                    
+                    field.dispatchEvent(new KeyboardEvent('keyup', {{ 
+                        key: char,
+                        bubbles: true
+                    }}));
+                }}
+                
+                await new Promise(r => setTimeout(r, 250));
+                field.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                field.dispatchEvent(new Event('blur', {{ bubbles: true }}));
+                
+                return true;
+            }} catch(e) {{
+                console.error('Type error:', e);
+                return false;
+            }}
+        }})()
+        "#,
+        serde_json::to_string(selector)?,
+        serde_json::to_string(text)?,
+        base_delay,
+        variance
+    )).await?;
+
+    Ok(result)
+}
+
+pub async fn click_element(
+    page: &Page,
+    selector: &str,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    click_element_in(page, None, selector).await
+}
+
+pub async fn click_element_in(
+    page: &Page,
+    context: Option<ExecutionContextId>,
+    selector: &str,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    info!("Clicking element: {}", selector);
+    
+    let clicked: bool = eval_in(page, context, format!(
+        r#"
+        (() => {{
+            try {{
+                const el = document.querySelector({});
+                if (!el || el.offsetParent === null) return false;
+                el.scrollIntoView({{ block: 'center' }});
+                el.click();
+                return true;
+            }} catch(e) {{
+                return false;
+            }}
+        }})()
+        "#,
+        serde_json::to_string(selector)?
+    )).await?;
+    
+    Ok(clicked)
+}
+
+pub async fn press_enter(
+    page: &Page,
+    selector: &str,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let pressed: bool = page.evaluate(format!(
+        r#"
+        (() => {{
+            const el = document.querySelector({});
+            if (!el) return false;
+            const opts = {{ key: 'Enter', code: 'Enter', keyCode: 13, which: 13, bubbles: true }};
+            el.dispatchEvent(new KeyboardEvent('keydown', opts));
+            el.dispatchEvent(new KeyboardEvent('keypress', opts));
+            el.dispatchEvent(new KeyboardEvent('keyup', opts));
+            if (el.form) {{
+                if (el.form.requestSubmit) el.form.requestSubmit(); else el.form.submit();
+            }}
+            return true;
+        }})()
+        "#,
+        serde_json::to_string(selector)?
+    )).await?.into_value()?;
+    
+    Ok(pressed)
+}
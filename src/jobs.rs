@@ -0,0 +1,138 @@
+use rand::Rng;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Job {
+    pub id: String,
+    pub status: JobStatus,
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub results: Vec<serde_json::Value>,
+    pub error: Option<String>,
+    #[serde(skip)]
+    finished_at: Option<Instant>,
+}
+
+pub struct JobStore {
+    jobs: Mutex<HashMap<String, Job>>,
+    ttl: Duration,
+}
+
+impl JobStore {
+    fn new(ttl: Duration) -> Self {
+        Self { jobs: Mutex::new(HashMap::new()), ttl }
+    }
+
+    fn evict_expired(&self, map: &mut HashMap<String, Job>) {
+        map.retain(|_, job| job.finished_at.is_none_or(|at| at.elapsed() < self.ttl));
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut Job)) {
+        if let Ok(mut map) = self.jobs.lock()
+            && let Some(job) = map.get_mut(id)
+        {
+            apply(job);
+        }
+    }
+
+    pub fn create(&self, total: usize) -> String {
+        let id = {
+            let mut rng = rand::rng();
+            format!("{:016x}{:016x}", rng.random::<u64>(), rng.random::<u64>())
+        };
+        if let Ok(mut map) = self.jobs.lock() {
+            self.evict_expired(&mut map);
+            map.insert(
+                id.clone(),
+                Job {
+                    id: id.clone(),
+                    status: JobStatus::Pending,
+                    total,
+                    completed: 0,
+                    failed: 0,
+                    results: Vec::new(),
+                    error: None,
+                    finished_at: None,
+                },
+            );
+        }
+        id
+    }
+
+    pub fn start(&self, id: &str) {
+        self.update(id, |job| job.status = JobStatus::Running);
+    }
+
+    pub fn record(&self, id: &str, result: serde_json::Value, success: bool) {
+        self.update(id, |job| {
+            if success {
+                job.completed += 1;
+            } else {
+                job.failed += 1;
+            }
+            job.total = job.total.max(job.completed + job.failed);
+            job.results.push(result);
+        });
+    }
+
+    pub fn finish(&self, id: &str, error: Option<String>) {
+        self.update(id, |job| {
+            let all_failed = job.total > 0 && job.failed == job.total;
+            job.status = if error.is_some() || all_failed { JobStatus::Failed } else { JobStatus::Completed };
+            job.error = error;
+            job.finished_at = Some(Instant::now());
+        });
+    }
+
+    pub fn get(&self, id: &str) -> Option<Job> {
+        let mut map = self.jobs.lock().ok()?;
+        self.evict_expired(&mut map);
+        map.get(id).cloned()
+    }
+}
+
+fn job_ttl() -> Duration {
+    let secs = std::env::var("JOB_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3600);
+    Duration::from_secs(secs)
+}
+
+pub fn store() -> &'static JobStore {
+    static STORE: OnceLock<JobStore> = OnceLock::new();
+    STORE.get_or_init(|| JobStore::new(job_ttl()))
+}
+
+pub fn create(total: usize) -> String {
+    store().create(total)
+}
+
+pub fn start(id: &str) {
+    store().start(id)
+}
+
+pub fn record(id: &str, result: serde_json::Value, success: bool) {
+    store().record(id, result, success)
+}
+
+pub fn finish(id: &str, error: Option<String>) {
+    store().finish(id, error)
+}
+
+pub fn get(id: &str) -> Option<Job> {
+    store().get(id)
+}
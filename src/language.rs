@@ -0,0 +1,44 @@
+const ISO_639_1: &[(&str, &str)] = &[
+    ("afr", "af"), ("aka", "ak"), ("amh", "am"), ("ara", "ar"), ("aze", "az"),
+    ("bel", "be"), ("ben", "bn"), ("bul", "bg"), ("cat", "ca"), ("ces", "cs"),
+    ("cmn", "zh"), ("dan", "da"), ("deu", "de"), ("ell", "el"), ("eng", "en"),
+    ("epo", "eo"), ("est", "et"), ("fin", "fi"), ("fra", "fr"), ("guj", "gu"),
+    ("heb", "he"), ("hin", "hi"), ("hrv", "hr"), ("hun", "hu"), ("hye", "hy"),
+    ("ind", "id"), ("ita", "it"), ("jav", "jv"), ("jpn", "ja"), ("kan", "kn"),
+    ("kat", "ka"), ("khm", "km"), ("kor", "ko"), ("lat", "la"), ("lav", "lv"),
+    ("lit", "lt"), ("mal", "ml"), ("mar", "mr"), ("mkd", "mk"), ("mya", "my"),
+    ("nep", "ne"), ("nld", "nl"), ("nob", "nb"), ("ori", "or"), ("pan", "pa"),
+    ("pes", "fa"), ("pol", "pl"), ("por", "pt"), ("ron", "ro"), ("rus", "ru"),
+    ("sin", "si"), ("slk", "sk"), ("slv", "sl"), ("sna", "sn"), ("spa", "es"),
+    ("srp", "sr"), ("swe", "sv"), ("tam", "ta"), ("tel", "te"), ("tgl", "tl"),
+    ("tha", "th"), ("tuk", "tk"), ("tur", "tr"), ("ukr", "uk"), ("urd", "ur"),
+    ("uzb", "uz"), ("vie", "vi"), ("yid", "yi"), ("zul", "zu"),
+];
+
+fn from_html_lang(lang: &str) -> Option<String> {
+    let primary = lang.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+    if primary.len() == 2 && primary.chars().all(|c| c.is_ascii_alphabetic()) {
+        Some(primary)
+    } else {
+        ISO_639_1
+            .iter()
+            .find(|(long, _)| *long == primary)
+            .map(|(_, short)| short.to_string())
+    }
+}
+
+pub fn detect_language(html_lang: Option<&str>, text: &str) -> Option<(String, f64)> {
+    if let Some(lang) = html_lang.and_then(from_html_lang) {
+        return Some((lang, 1.0));
+    }
+    if text.trim().is_empty() {
+        return None;
+    }
+
+    let info = whatlang::detect(text)?;
+    let code = ISO_639_1
+        .iter()
+        .find(|(long, _)| *long == info.lang().code())
+        .map(|(_, short)| short.to_string())?;
+    Some((code, info.confidence()))
+}
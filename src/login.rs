@@ -1,19 +1,33 @@
 use crate::model::{LoginCredentials, CookieData};
-use crate::config::{get_platform_config, PlatformConfig};
-use chromiumoxide::{Page, cdp::browser_protocol::network::SetCookieParams};
+use crate::config::get_platform_config;
+use crate::errors::ScrapeError;
+use crate::metrics;
+use crate::interactions::{
+    click_element_in, eval_in, type_into_field, type_into_field_in, wait_for_any_element,
+};
+use crate::progress::emit;
+use crate::scraper::platform_for_user_agent;
+use chromiumoxide::Page;
+use chromiumoxide::cdp::browser_protocol::network::{
+    Cookie, CookieSameSite, SetCookieParams, TimeSinceEpoch,
+};
+use chromiumoxide::cdp::browser_protocol::storage::GetCookiesParams;
 use chromiumoxide::cdp::browser_protocol::emulation::{SetUserAgentOverrideParams, SetTimezoneOverrideParams};
-use chromiumoxide::cdp::browser_protocol::page::SetWebLifecycleStateParams;
+use chromiumoxide::cdp::browser_protocol::page::{CreateIsolatedWorldParams, FrameTree, GetFrameTreeParams};
+use chromiumoxide::cdp::js_protocol::runtime::ExecutionContextId;
 use tokio::time::{sleep, Duration};
 use std::error::Error;
-use tracing::{info, warn, error, debug, instrument, info_span};
+use totp_lite::{totp_custom, Sha1, DEFAULT_STEP};
+use tracing::{info, warn, error, debug, instrument};
 
 async fn setup_stealth_mode(page: &Page) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let user_agent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    let current: String = page.evaluate("navigator.userAgent").await?.into_value()?;
+    let user_agent = current.replace("HeadlessChrome", "Chrome");
     
     let ua_params = SetUserAgentOverrideParams {
-        user_agent: user_agent.to_string(),
+        platform: Some(platform_for_user_agent(&user_agent).to_string()),
+        user_agent,
         accept_language: Some("en-US,en;q=0.9".to_string()),
-        platform: Some("Win32".to_string()),
         user_agent_metadata: None,
     };
     page.execute(ua_params).await?;
@@ -97,6 +111,31 @@ async fn setup_stealth_mode(page: &Page) -> Result<(), Box<dyn Error + Send + Sy
     Ok(())
 }
 
+fn cookie_target(cookie: &CookieData) -> (String, Option<String>) {
+    let raw = cookie.domain.trim();
+    let host = raw
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches('.')
+        .split(['/', ':'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let path = match cookie.path.as_deref() {
+        Some(p) if p.starts_with('/') => p.to_string(),
+        Some(p) if !p.is_empty() => format!("/{}", p),
+        _ => "/".to_string(),
+    };
+    let scheme = if cookie.secure == Some(false) { "http" } else { "https" };
+    let url = format!("{}://{}{}", scheme, host, path);
+    
+    if raw.starts_with('.') {
+        (url, Some(format!(".{}", host)))
+    } else {
+        (url, None)
+    }
+}
+
 #[instrument(skip(page, cookies), fields(count = cookies.len()))]
 pub async fn set_cookies(
     page: &Page,
@@ -107,19 +146,24 @@ pub async fn set_cookies(
     let mut success_count = 0;
     
     for cookie in cookies {
-        let domain_clean = cookie.domain.trim_start_matches('.');
-        let cookie_url = format!("https://{}{}", domain_clean, cookie.path.as_deref().unwrap_or("/"));
+        let (cookie_url, cookie_domain) = cookie_target(cookie);
         
         let cookie_params = SetCookieParams {
             name: cookie.name.clone(),
             value: cookie.value.clone(),
             url: Some(cookie_url),
-            domain: Some(cookie.domain.clone()),
+            domain: cookie_domain,
             path: cookie.path.clone(),
-            secure: Some(true),
-            http_only: None,
-            same_site: Some(chromiumoxide::cdp::browser_protocol::network::CookieSameSite::Lax),
-            expires: None,
+            secure: Some(cookie.secure.unwrap_or(true)),
+            http_only: cookie.http_only,
+            same_site: Some(
+                cookie
+                    .same_site
+                    .as_deref()
+                    .and_then(|s| s.parse::<CookieSameSite>().ok())
+                    .unwrap_or(CookieSameSite::Lax),
+            ),
+            expires: cookie.expires.map(TimeSinceEpoch::new),
             priority: None,
             same_party: None,
             source_scheme: None,
@@ -141,8 +185,109 @@ pub async fn set_cookies(
     Ok(success_count > 0)
 }
 
+pub async fn get_cookies(page: &Page) -> Result<Vec<CookieData>, Box<dyn Error + Send + Sync>> {
+    let cookies = page.get_cookies().await?;
+    
+    Ok(cookies.into_iter().map(cookie_data).collect())
+}
+
+pub async fn get_all_cookies(page: &Page) -> Result<Vec<CookieData>, Box<dyn Error + Send + Sync>> {
+    let cookies = page.execute(GetCookiesParams::default()).await?.result.cookies;
+    
+    Ok(cookies.into_iter().map(cookie_data).collect())
+}
+
+fn cookie_data(c: Cookie) -> CookieData {
+    CookieData {
+        name: c.name,
+        value: c.value,
+        domain: c.domain,
+        path: Some(c.path),
+        secure: Some(c.secure),
+        http_only: Some(c.http_only),
+        same_site: c.same_site.map(|s| s.as_ref().to_string()),
+        expires: if c.session { None } else { Some(c.expires) },
+    }
+}
+
+pub fn parse_cookies_txt(text: &str) -> Vec<CookieData> {
+    let mut cookies = Vec::new();
+    
+    for (index, raw) in text.lines().enumerate() {
+        let line = raw.trim_end_matches('\r');
+        let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+            Some(rest) => (rest, true),
+            None => (line, false),
+        };
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 7 {
+            warn!("Skipping malformed cookies.txt line {}: expected 7 fields, found {}", index + 1, fields.len());
+            continue;
+        }
+        let Ok(expires) = fields[4].trim().parse::<f64>() else {
+            warn!("Skipping malformed cookies.txt line {}: invalid expiry {:?}", index + 1, fields[4]);
+            continue;
+        };
+        
+        let host = fields[0].trim();
+        let include_subdomains = fields[1].trim().eq_ignore_ascii_case("TRUE");
+        let domain = if include_subdomains && !host.starts_with('.') {
+            format!(".{}", host)
+        } else {
+            host.to_string()
+        };
+        if domain.trim_start_matches('.').is_empty() || fields[5].is_empty() {
+            warn!("Skipping malformed cookies.txt line {}: missing domain or name", index + 1);
+            continue;
+        }
+        
+        cookies.push(CookieData {
+            name: fields[5].to_string(),
+            value: fields[6..].join("\t"),
+            domain,
+            path: Some(fields[2].trim().to_string()).filter(|p| !p.is_empty()),
+            secure: Some(fields[3].trim().eq_ignore_ascii_case("TRUE")),
+            http_only: Some(http_only),
+            same_site: None,
+            expires: if expires > 0.0 { Some(expires) } else { None },
+        });
+    }
+    
+    cookies
+}
+
+fn credential_cookies(credentials: &LoginCredentials) -> Option<Vec<CookieData>> {
+    let mut cookies = credentials.cookies.clone().unwrap_or_default();
+    if let Some(text) = &credentials.cookies_txt {
+        let parsed = parse_cookies_txt(text);
+        info!("Parsed {} cookies from cookies.txt", parsed.len());
+        for cookie in parsed {
+            let duplicate = cookies.iter().any(|c| {
+                c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path
+            });
+            if !duplicate {
+                cookies.push(cookie);
+            }
+        }
+    }
+    
+    if cookies.is_empty() { None } else { Some(cookies) }
+}
+
 async fn verify_authentication(page: &Page, platform: &str) -> Result<bool, Box<dyn Error + Send + Sync>> {
-    info!("Verifying authentication status");
+    info!("Verifying authentication status for {}", platform);
+    
+    if let Some(checks) = get_platform_config(platform).additional_checks {
+        let checks: Vec<String> = checks.iter().map(|s| s.to_string()).collect();
+        if let Some(found) = wait_for_any_element(page, None, &checks, 1500).await? {
+            info!("Authentication verified by {} indicator {}", platform, found);
+            return Ok(true);
+        }
+    }
     
     let is_authenticated = page.evaluate(
         r#"
@@ -196,6 +341,105 @@ async fn verify_authentication(page: &Page, platform: &str) -> Result<bool, Box<
     Ok(is_authenticated)
 }
 
+async fn eval_check(page: &Page, js: &str) -> bool {
+    let result = page.evaluate(js).await
+        .ok()
+        .and_then(|v| v.into_value::<bool>().ok())
+        .unwrap_or(false);
+    debug!("Custom check `{}` returned {}", js, result);
+    result
+}
+
+pub async fn check_authenticated(
+    page: &Page,
+    platform: &str,
+    credentials: &LoginCredentials,
+) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    match &credentials.success_check_js {
+        Some(js) => Ok(eval_check(page, js).await),
+        None => verify_authentication(page, platform).await,
+    }
+}
+
+const OTP_SELECTORS: &[&str] = &[
+    "input[autocomplete='one-time-code']",
+    "input[name*='otp' i]",
+    "input[id*='otp' i]",
+    "input[name*='totp' i]",
+    "input[name*='code' i]",
+    "input[id*='code' i]",
+    "input[inputmode='numeric']",
+    "input[type='tel']",
+];
+
+async fn requires_2fa(page: &Page) -> bool {
+    page.evaluate(
+        "document.body.innerText.toLowerCase().includes('verification') || document.body.innerText.toLowerCase().includes('two-factor') || document.body.innerText.toLowerCase().includes('code')"
+    ).await.ok().and_then(|v| v.into_value::<bool>().ok()).unwrap_or(false)
+}
+
+fn totp_code(secret: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+    totp_code_at(secret, now)
+}
+
+fn totp_code_at(secret: &str, unix_time: u64) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let normalized: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '=')
+        .collect::<String>()
+        .to_uppercase();
+    let key = base32::decode(base32::Alphabet::Rfc4648 { padding: false }, &normalized)
+        .ok_or("Invalid TOTP secret")?;
+    Ok(totp_custom::<Sha1>(DEFAULT_STEP, 6, &key, unix_time))
+}
+
+async fn submit_totp(page: &Page, secret: &str) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let selectors: Vec<String> = OTP_SELECTORS.iter().map(|s| s.to_string()).collect();
+    let Some(sel) = wait_for_any_element(page, None, &selectors, 5000).await? else {
+        warn!("2FA prompt detected but no code input found");
+        return Ok(false);
+    };
+    emit("login", "Entering TOTP code");
+    let code = totp_code(secret)?;
+    if !type_into_field(page, &sel, &code).await? {
+        return Ok(false);
+    }
+    sleep(Duration::from_millis(400)).await;
+    let _ = page.evaluate(format!(
+        r#"
+        (() => {{
+            const buttons = document.querySelectorAll('button, input[type="submit"]');
+            for (const btn of buttons) {{
+                const text = (btn.textContent || btn.value || '').toLowerCase();
+                if (btn.offsetParent !== null && (btn.type === 'submit' || text.includes('verify') || text.includes('continue') || text.includes('submit'))) {{
+                    btn.click();
+                    return true;
+                }}
+            }}
+            const field = document.querySelector({});
+            if (field) {{
+                field.dispatchEvent(new KeyboardEvent('keydown', {{key: 'Enter', keyCode: 13, bubbles: true}}));
+                if (field.form) field.form.requestSubmit ? field.form.requestSubmit() : field.form.submit();
+                return true;
+            }}
+            return false;
+        }})()
+        "#,
+        serde_json::to_string(&sel).unwrap_or_default()
+    )).await;
+    sleep(Duration::from_secs(5)).await;
+    log_page_state(page, "after_totp").await?;
+
+    let still_prompted = wait_for_any_element(page, None, &selectors, 500).await?.is_some()
+        && requires_2fa(page).await;
+    if still_prompted {
+        warn!("TOTP code was rejected");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
 async fn log_page_state(page: &Page, context: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
     let url = page.url().await.ok().flatten().unwrap_or_else(|| "unknown".to_string());
     let title = page.evaluate("document.title").await
@@ -209,129 +453,33 @@ async fn log_page_state(page: &Page, context: &str) -> Result<(), Box<dyn Error
     Ok(())
 }
 
-async fn wait_for_any_element(
+async fn find_login_frame(
     page: &Page,
     selectors: &[String],
-    timeout_ms: u64,
-) -> Result<Option<String>, Box<dyn Error + Send + Sync>> {
-    let check_interval_ms = 300;
-    let mut elapsed = 0;
-
-    debug!("Searching for: {:?}", selectors);
-
-    while elapsed < timeout_ms {
-        for selector in selectors {
-            let is_visible: bool = page.evaluate(format!(
-                r#"
-                (() => {{
-                    try {{
-                        const el = document.querySelector('{}');
-                        if (!el) return false;
-                        
-                        const style = window.getComputedStyle(el);
-                        const rect = el.getBoundingClientRect();
-                        
-                        return el.offsetParent !== null &&
-                               style.visibility !== 'hidden' && 
-                               style.display !== 'none' && 
-                               parseFloat(style.opacity) > 0 &&
-                               rect.width > 0 && 
-                               rect.height > 0;
-                    }} catch(e) {{
-                        return false;
-                    }}
-                }})()
-                "#,
-                selector.replace("'", "\\'").replace("\\", "\\\\")
-            )).await.ok().and_then(|v| v.into_value().ok()).unwrap_or(false);
-
-            if is_visible {
-                info!("Found element: {}", selector);
-                return Ok(Some(selector.clone()));
-            }
+) -> Result<Option<(String, ExecutionContextId, String)>, Box<dyn Error + Send + Sync>> {
+    let tree = page.execute(GetFrameTreeParams::default()).await?.result.frame_tree;
+    let mut pending: Vec<FrameTree> = tree.child_frames.unwrap_or_default();
+
+    while let Some(FrameTree { frame, child_frames }) = pending.pop() {
+        pending.extend(child_frames.unwrap_or_default());
+        debug!("Probing frame for login form: {}", frame.url);
+
+        let world = CreateIsolatedWorldParams::builder()
+            .frame_id(frame.id.clone())
+            .world_name("login_frame")
+            .build()?;
+        let Ok(world) = page.execute(world).await else {
+            continue;
+        };
+        let context = world.result.execution_context_id;
+        if let Some(selector) = wait_for_any_element(page, Some(context), selectors, 1500).await? {
+            return Ok(Some((frame.url, context, selector)));
         }
-        
-        sleep(Duration::from_millis(check_interval_ms)).await;
-        elapsed += check_interval_ms;
     }
 
     Ok(None)
 }
 
-async fn type_into_field(
-    page: &Page,
-    selector: &str,
-    text: &str,
-) -> Result<bool, Box<dyn Error + Send + Sync>> {
-    info!("Typing into field: {}", selector);
-    
-    let base_delay = 60;
-    let variance = 40;
-    
-    let result: bool = page.evaluate(format!(
-        r#"
-        (async () => {{
-            try {{
-                const field = document.querySelector('{}');
-                if (!field || field.offsetParent === null) return false;
-                
-                field.scrollIntoView({{ behavior: 'smooth', block: 'center' }});
-                await new Promise(r => setTimeout(r, 400));
-                
-                field.focus();
-                field.click();
-                await new Promise(r => setTimeout(r, 150));
-                
-                field.value = '';
-                field.dispatchEvent(new Event('focus', {{ bubbles: true }}));
-                
-                const text = '{}';
-                
-                for (let i = 0; i < text.length; i++) {{
-                    const char = text.charAt(i);
-                    const delay = {} + Math.floor(Math.random() * {});
-                    
-                    await new Promise(r => setTimeout(r, delay));
-                    
-                    field.value += char;
-                    
-                    field.dispatchEvent(new InputEvent('input', {{ 
-                        data: char,
-                        inputType: 'insertText',
-                        bubbles: true
-                    }}));
-                    
-                    field.dispatchEvent(new KeyboardEvent('keydown', {{ 
-                        key: char,
-                        bubbles: true
-                    }}));
-                    
-                    field.dispatchEvent(new KeyboardEvent('keyup', {{ 
-                        key: char,
-                        bubbles: true
-                    }}));
-                }}
-                
-                await new Promise(r => setTimeout(r, 250));
-                field.dispatchEvent(new Event('change', {{ bubbles: true }}));
-                field.dispatchEvent(new Event('blur', {{ bubbles: true }}));
-                
-                return true;
-            }} catch(e) {{
-                console.error('Type error:', e);
-                return false;
-            }}
-        }})()
-        "#,
-        selector.replace("'", "\\'").replace("\\", "\\\\"),
-        text.replace("'", "\\'").replace("\\", "\\\\").replace("\n", "\\n"),
-        base_delay,
-        variance
-    )).await?.into_value()?;
-
-    Ok(result)
-}
-
 async fn dismiss_overlays(page: &Page) -> Result<(), Box<dyn Error + Send + Sync>> {
     for _ in 0..3 {
         let dismissed = page.evaluate(
@@ -369,6 +517,68 @@ async fn dismiss_overlays(page: &Page) -> Result<(), Box<dyn Error + Send + Sync
     Ok(())
 }
 
+pub(crate) trait Navigator {
+    async fn location(&self) -> String;
+    async fn navigate_to(&self, url: &str) -> Result<(), ScrapeError>;
+}
+
+impl Navigator for Page {
+    async fn location(&self) -> String {
+        self.url().await.ok().flatten().unwrap_or_default()
+    }
+
+    async fn navigate_to(&self, url: &str) -> Result<(), ScrapeError> {
+        self.goto(url)
+            .await
+            .map(|_| ())
+            .map_err(|e| ScrapeError::Navigation(e.to_string()))
+    }
+}
+
+pub(crate) async fn land_on_target(nav: &impl Navigator, target_url: &str) -> Result<bool, ScrapeError> {
+    if is_same_page(&nav.location().await, target_url) {
+        debug!("Already on {}, skipping navigation", target_url);
+        return Ok(false);
+    }
+    nav.navigate_to(target_url).await?;
+    Ok(true)
+}
+
+pub fn is_same_page(current_url: &str, target_url: &str) -> bool {
+    let page_key = |raw: &str| {
+        let url = reqwest::Url::parse(raw.trim()).ok()?;
+        Some((
+            url.scheme().to_string(),
+            url.host_str()?.to_string(),
+            url.port_or_known_default(),
+            url.path().trim_end_matches('/').to_string(),
+            url.query().map(str::to_string),
+        ))
+    };
+    match (page_key(current_url), page_key(target_url)) {
+        (Some(current), Some(target)) => current == target,
+        _ => false,
+    }
+}
+
+async fn match_platform_candidate<'a>(
+    page: &Page,
+    candidates: &'a [String],
+) -> Result<Option<&'a str>, Box<dyn Error + Send + Sync>> {
+    for candidate in candidates {
+        let selectors: Vec<String> = get_platform_config(candidate)
+            .email_selectors
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        debug!("Probing platform candidate: {}", candidate);
+        if wait_for_any_element(page, None, &selectors, 1500).await?.is_some() {
+            return Ok(Some(candidate.as_str()));
+        }
+    }
+    Ok(None)
+}
+
 fn get_login_url(platform: &str, target_url: &str) -> String {
     match platform {
         "google" => "https://accounts.google.com/ServiceLogin".to_string(),
@@ -383,17 +593,29 @@ fn get_login_url(platform: &str, target_url: &str) -> String {
     }
 }
 
+pub type LoginOutcome = (bool, Option<String>, Option<bool>, Option<String>);
+
 #[instrument(skip(page, credentials), fields(platform, target = target_url))]
 pub async fn auto_login(
     page: &Page,
     credentials: &LoginCredentials,
     target_url: &str,
-) -> Result<(bool, Option<String>, Option<bool>), Box<dyn Error + Send + Sync>> {
-    info!("Starting authentication");
+) -> Result<LoginOutcome, Box<dyn Error + Send + Sync>> {
+    let outcome = perform_login(page, credentials, target_url).await;
+    metrics::record_login(matches!(outcome, Ok((true, ..))));
+    outcome
+}
+
+async fn perform_login(
+    page: &Page,
+    credentials: &LoginCredentials,
+    target_url: &str,
+) -> Result<LoginOutcome, Box<dyn Error + Send + Sync>> {
+    emit("login", "Starting authentication");
     
     setup_stealth_mode(page).await?;
     
-    let platform = credentials.platform.as_deref().unwrap_or_else(|| {
+    let mut platform = credentials.platform.as_deref().unwrap_or_else(|| {
         if target_url.contains("google.com") || target_url.contains("gmail.com") { "google" }
         else if target_url.contains("linkedin.com") { "linkedin" }
         else if target_url.contains("reddit.com") { "reddit" }
@@ -403,48 +625,61 @@ pub async fn auto_login(
         else if target_url.contains("instagram.com") { "instagram" }
         else { "generic" }
     });
-    info!("Platform: {}", platform);
+    emit("login", format!("Platform: {}", platform));
     
-    let config = get_platform_config(platform);
+    let mut config = get_platform_config(platform);
+    let candidates = credentials.platform_candidates.as_deref().unwrap_or(&[]);
     
-    if let Some(cookies) = &credentials.cookies {
+    if let Some(cookies) = credential_cookies(credentials) {
         info!("Attempting cookie-based authentication");
         
-        if set_cookies(page, cookies).await? {
+        if set_cookies(page, &cookies).await? {
             info!("Navigating to verify cookies: {}", target_url);
             page.goto(target_url).await?;
             sleep(Duration::from_millis(3000)).await;
             
             log_page_state(page, "after_cookies").await?;
             
-            if verify_authentication(page, platform).await? {
+            if check_authenticated(page, platform, credentials).await? {
                 info!("Cookie authentication successful");
-                return Ok((true, Some(platform.to_string()), Some(false)));
+                return Ok((true, Some(platform.to_string()), Some(false), None));
             } else {
                 warn!("Cookies did not authenticate, falling back to form login");
             }
         }
     }
     
-    info!("Starting form-based login");
+    emit("login", "Starting form-based login");
     
     let login_url = credentials.login_url.as_deref()
         .map(|s| s.to_string())
         .unwrap_or_else(|| {
-            if !config.login_url.is_empty() {
-                config.login_url.clone().to_string()
+            if !candidates.is_empty() {
+                target_url.to_string()
+            } else if !config.login_url.is_empty() {
+                config.login_url.to_string()
             } else {
                 get_login_url(platform, target_url)
             }
         });
     
-    info!("Navigating to: {}", login_url);
+    emit("login", format!("Navigating to: {}", login_url));
     page.goto(&login_url).await?;
     sleep(Duration::from_millis(2500)).await;
     
     log_page_state(page, "login_page").await?;
     dismiss_overlays(page).await?;
     
+    if !candidates.is_empty() {
+        if let Some(matched) = match_platform_candidate(page, candidates).await? {
+            info!("Platform candidate matched: {}", matched);
+            platform = matched;
+            config = get_platform_config(platform);
+        } else {
+            warn!("No platform candidate matched, keeping {}", platform);
+        }
+    }
+    
     let email_selectors: Vec<String> = if let Some(sel) = &credentials.email_selector {
         vec![sel.clone()]
     } else if !config.email_selectors.is_empty() {
@@ -458,7 +693,7 @@ pub async fn auto_login(
         }
     };
     
-    let password_selectors: Vec<String> = if let Some(sel) = &credentials.password_selector {
+    let mut password_selectors: Vec<String> = if let Some(sel) = &credentials.password_selector {
         vec![sel.clone()]
     } else if !config.password_selectors.is_empty() {
         config.password_selectors.iter().map(|s| s.to_string()).collect()
@@ -471,10 +706,33 @@ pub async fn auto_login(
         }
     };
     
-    info!("Entering email/username");
-    let email_sel = wait_for_any_element(page, &email_selectors, 15000).await?;
+    emit("login", "Entering email/username");
+    let mut email_sel = if credentials.login_in_iframe {
+        None
+    } else {
+        wait_for_any_element(page, None, &email_selectors, 15000).await?
+    };
+    if email_sel.is_none() && credentials.email_selector.is_none() && platform != "generic" && !credentials.login_in_iframe {
+        let generic = get_platform_config("generic");
+        let generic_email: Vec<String> = generic.email_selectors.iter().map(|s| s.to_string()).collect();
+        warn!("Email field not found with {} selectors, retrying with generic selectors", platform);
+        email_sel = wait_for_any_element(page, None, &generic_email, 5000).await?;
+        if email_sel.is_some() && credentials.password_selector.is_none() {
+            password_selectors = generic.password_selectors.iter().map(|s| s.to_string()).collect();
+        }
+    }
+    let mut context = None;
+    let mut login_frame = None;
+    if email_sel.is_none()
+        && let Some((frame_url, frame_context, sel)) = find_login_frame(page, &email_selectors).await?
+    {
+        emit("login", format!("Login form found in iframe: {}", frame_url));
+        context = Some(frame_context);
+        login_frame = Some(frame_url);
+        email_sel = Some(sel);
+    }
     if let Some(sel) = email_sel {
-        if !type_into_field(page, &sel, &credentials.email).await? {
+        if !type_into_field_in(page, context, &sel, &credentials.email).await? {
             return Err("Failed to enter email".into());
         }
         sleep(Duration::from_millis(600)).await;
@@ -483,39 +741,54 @@ pub async fn auto_login(
         return Err("Email field not found".into());
     }
     
-    let is_multi_step = ["google", "linkedin", "twitter", "facebook"].contains(&platform);
+    let is_multi_step = credentials.next_selector.is_some()
+        || ["google", "linkedin", "twitter", "facebook"].contains(&platform);
     if is_multi_step {
-        let pass_visible = wait_for_any_element(page, &password_selectors, 2000).await?.is_some();
+        let pass_visible = wait_for_any_element(page, context, &password_selectors, 2000).await?.is_some();
         if !pass_visible {
-            info!("Multi-step detected, clicking Next");
-            let _ = page.evaluate(
-                r#"
-                (() => {
-                    const buttons = document.querySelectorAll('button, input[type="submit"]');
-                    for (const btn of buttons) {
-                        const text = (btn.textContent || btn.value || '').toLowerCase();
-                        if ((text.includes('next') || text.includes('continue')) && btn.offsetParent !== null) {
-                            btn.click();
+            emit("login", "Multi-step detected, clicking Next");
+            let clicked_next = match &credentials.next_selector {
+                Some(sel) => {
+                    let clicked = click_element_in(page, context, sel).await?;
+                    if !clicked {
+                        warn!("Next button not found with {}, falling back to heuristic", sel);
+                    }
+                    clicked
+                }
+                None => false,
+            };
+            if !clicked_next {
+                let _ = eval_in::<bool>(
+                    page,
+                    context,
+                    r#"
+                    (() => {
+                        const buttons = document.querySelectorAll('button, input[type="submit"]');
+                        for (const btn of buttons) {
+                            const text = (btn.textContent || btn.value || '').toLowerCase();
+                            if ((text.includes('next') || text.includes('continue')) && btn.offsetParent !== null) {
+                                btn.click();
+                                return true;
+                            }
+                        }
+                        const emailInput = document.querySelector('input[type="email"]');
+                        if (emailInput) {
+                            emailInput.dispatchEvent(new KeyboardEvent('keydown', {key: 'Enter', keyCode: 13, bubbles: true}));
                             return true;
                         }
-                    }
-                    const emailInput = document.querySelector('input[type="email"]');
-                    if (emailInput) {
-                        emailInput.dispatchEvent(new KeyboardEvent('keydown', {key: 'Enter', keyCode: 13, bubbles: true}));
-                        return true;
-                    }
-                    return false;
-                })()
-                "#
-            ).await;
+                        return false;
+                    })()
+                    "#
+                ).await;
+            }
             sleep(Duration::from_millis(3000)).await;
         }
     }
     
-    info!("Entering password");
-    let pass_sel = wait_for_any_element(page, &password_selectors, 15000).await?;
+    emit("login", "Entering password");
+    let pass_sel = wait_for_any_element(page, context, &password_selectors, 15000).await?;
     if let Some(sel) = pass_sel {
-        if !type_into_field(page, &sel, &credentials.password).await? {
+        if !type_into_field_in(page, context, &sel, &credentials.password).await? {
             return Err("Failed to enter password".into());
         }
         sleep(Duration::from_millis(600)).await;
@@ -524,85 +797,315 @@ pub async fn auto_login(
         return Err("Password field not found".into());
     }
     
-    info!("Submitting form");
-    let submitted = page.evaluate(
-        r#"
-        (() => {
-            const submitButtons = document.querySelectorAll('button[type="submit"], input[type="submit"]');
-            for (const btn of submitButtons) {
-                if (btn.offsetParent !== null) {
-                    btn.click();
-                    return true;
+    emit("login", "Submitting form");
+    let submit_selectors: Vec<String> = match &credentials.submit_selector {
+        Some(sel) => vec![sel.clone()],
+        None => config.submit_selectors.iter().map(|s| s.to_string()).collect(),
+    };
+    let mut submitted = match wait_for_any_element(page, context, &submit_selectors, 2000).await? {
+        Some(sel) => click_element_in(page, context, &sel).await?,
+        None => false,
+    };
+    if !submitted {
+        submitted = eval_in::<bool>(
+            page,
+            context,
+            r#"
+            (() => {
+                const submitButtons = document.querySelectorAll('button[type="submit"], input[type="submit"]');
+                for (const btn of submitButtons) {
+                    if (btn.offsetParent !== null) {
+                        btn.click();
+                        return true;
+                    }
                 }
-            }
             
-            const buttons = document.querySelectorAll('button');
-            for (const btn of buttons) {
-                const text = (btn.textContent || '').toLowerCase();
-                if ((text.includes('sign in') || text.includes('log in') || text.includes('login')) && 
-                    btn.offsetParent !== null) {
-                    btn.click();
-                    return true;
+                const buttons = document.querySelectorAll('button');
+                for (const btn of buttons) {
+                    const text = (btn.textContent || '').toLowerCase();
+                    if ((text.includes('sign in') || text.includes('log in') || text.includes('login')) && 
+                        btn.offsetParent !== null) {
+                        btn.click();
+                        return true;
+                    }
                 }
-            }
             
-            const passField = document.querySelector('input[type="password"]');
-            if (passField) {
-                passField.dispatchEvent(new KeyboardEvent('keydown', {key: 'Enter', keyCode: 13, bubbles: true}));
-                return true;
-            }
+                const passField = document.querySelector('input[type="password"]');
+                if (passField) {
+                    passField.dispatchEvent(new KeyboardEvent('keydown', {key: 'Enter', keyCode: 13, bubbles: true}));
+                    return true;
+                }
             
-            const form = document.querySelector('form');
-            if (form) {
-                form.submit();
-                return true;
-            }
+                const form = document.querySelector('form');
+                if (form) {
+                    form.submit();
+                    return true;
+                }
             
-            return false;
-        })()
-        "#
-    ).await.ok().and_then(|v| v.into_value::<bool>().ok()).unwrap_or(false);
+                return false;
+            })()
+            "#
+        ).await.unwrap_or(false);
+    }
     
     if !submitted {
         warn!("Could not find submit button");
     }
     
-    info!("Waiting for response");
-    sleep(Duration::from_secs(5)).await;
+    let wait_after_login = credentials.wait_after_login_secs.unwrap_or(config.wait_after_login);
+    info!("Waiting {}s for response", wait_after_login);
+    sleep(Duration::from_secs(wait_after_login)).await;
     log_page_state(page, "after_submit").await?;
     
-    let requires_2fa = page.evaluate(
-        "document.body.innerText.toLowerCase().includes('verification') || document.body.innerText.toLowerCase().includes('two-factor') || document.body.innerText.toLowerCase().includes('code')"
-    ).await.ok().and_then(|v| v.into_value::<bool>().ok()).unwrap_or(false);
-    
-    if requires_2fa {
-        warn!("2FA required");
-        return Ok((false, Some(platform.to_string()), Some(true)));
+    if requires_2fa(page).await {
+        let passed = match &credentials.totp_secret {
+            Some(secret) => submit_totp(page, secret).await?,
+            None => false,
+        };
+        if !passed {
+            emit("login", "2FA required");
+            return Ok((false, Some(platform.to_string()), Some(true), login_frame));
+        }
     }
     
-    let has_error = page.evaluate(
-        "document.body.innerText.toLowerCase().includes('incorrect') || document.body.innerText.toLowerCase().includes('invalid') || document.body.innerText.toLowerCase().includes('wrong')"
-    ).await.ok().and_then(|v| v.into_value::<bool>().ok()).unwrap_or(false);
+    let has_error = match &credentials.error_check_js {
+        Some(js) => eval_check(page, js).await,
+        None => page.evaluate(
+            "document.body.innerText.toLowerCase().includes('incorrect') || document.body.innerText.toLowerCase().includes('invalid') || document.body.innerText.toLowerCase().includes('wrong')"
+        ).await.ok().and_then(|v| v.into_value::<bool>().ok()).unwrap_or(false),
+    };
     
     if has_error {
         error!("Login error detected");
-        return Ok((false, Some(platform.to_string()), Some(false)));
+        return Ok((false, Some(platform.to_string()), Some(false), login_frame));
     }
     
-    let is_authenticated = verify_authentication(page, platform).await?;
+    let is_authenticated = check_authenticated(page, platform, credentials).await?;
     
     if is_authenticated {
-        info!("Login successful");
+        emit("login", "Login successful");
         
-        let current_url = page.url().await.ok().flatten().unwrap_or_default();
-        if !target_url.contains(&current_url) && target_url != &login_url {
-            info!("Navigating to target: {}", target_url);
-            page.goto(target_url).await?;
+        if land_on_target(page, target_url).await? {
+            info!("Navigated to target: {}", target_url);
             sleep(Duration::from_millis(2000)).await;
         }
     } else {
         warn!("Login status unclear");
     }
     
-    Ok((is_authenticated, Some(platform.to_string()), Some(false)))
-}
\ No newline at end of file
+    Ok((is_authenticated, Some(platform.to_string()), Some(false), login_frame))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookie(domain: &str, path: Option<&str>, secure: Option<bool>) -> CookieData {
+        CookieData {
+            name: "sid".to_string(),
+            value: "abc".to_string(),
+            domain: domain.to_string(),
+            path: path.map(str::to_string),
+            secure,
+            http_only: None,
+            same_site: None,
+            expires: None,
+        }
+    }
+
+    const RFC_6238_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn totp_matches_rfc_6238_sha1_vectors() {
+        for (time, code) in [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1111111111, "050471"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+            (20000000000, "353130"),
+        ] {
+            assert_eq!(totp_code_at(RFC_6238_SECRET, time).unwrap(), code, "at {}", time);
+        }
+    }
+
+    #[test]
+    fn totp_accepts_formatted_secrets() {
+        for secret in [
+            "gezd gnbv gy3t qojq gezd gnbv gy3t qojq",
+            "GEZDGNBV-GY3TQOJQ-GEZDGNBV-GY3TQOJQ",
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ====",
+        ] {
+            assert_eq!(totp_code_at(secret, 1111111109).unwrap(), "081804", "{}", secret);
+        }
+    }
+
+    #[test]
+    fn totp_rejects_invalid_secrets() {
+        assert!(totp_code_at("not base32!", 59).is_err());
+    }
+
+    #[test]
+    fn cookies_txt_reads_http_only_prefix() {
+        let cookies = parse_cookies_txt("#HttpOnly_example.com\tFALSE\t/\tTRUE\t1999999999\tsid\tabc\n");
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].domain, "example.com");
+        assert_eq!(cookies[0].http_only, Some(true));
+        assert_eq!(cookies[0].secure, Some(true));
+        assert_eq!(cookies[0].expires, Some(1999999999.0));
+    }
+
+    #[test]
+    fn cookies_txt_keeps_subdomain_scope() {
+        let cookies = parse_cookies_txt(
+            ".example.com\tTRUE\t/\tFALSE\t0\ta\t1\nexample.org\tTRUE\t/app\tFALSE\t0\tb\t2\nexample.net\tFALSE\t/\tFALSE\t0\tc\t3\n",
+        );
+        let domains: Vec<&str> = cookies.iter().map(|c| c.domain.as_str()).collect();
+        assert_eq!(domains, [".example.com", ".example.org", "example.net"]);
+        assert_eq!(cookies[1].path.as_deref(), Some("/app"));
+        assert_eq!(cookies[0].http_only, Some(false));
+    }
+
+    #[test]
+    fn cookies_txt_treats_zero_expiry_as_session_cookie() {
+        let cookies = parse_cookies_txt("example.com\tFALSE\t/\tFALSE\t0\tsid\tabc");
+        assert_eq!(cookies[0].expires, None);
+    }
+
+    #[test]
+    fn cookies_txt_keeps_tabs_inside_values() {
+        let cookies = parse_cookies_txt("example.com\tFALSE\t/\tFALSE\t0\tprefs\ta\tb\tc\r\n");
+        assert_eq!(cookies[0].name, "prefs");
+        assert_eq!(cookies[0].value, "a\tb\tc");
+    }
+
+    #[test]
+    fn cookies_txt_skips_comments_and_malformed_lines() {
+        let text = "# Netscape HTTP Cookie File\n\
+                    \n\
+                    example.com\tFALSE\t/\tFALSE\n\
+                    example.com\tFALSE\t/\tFALSE\tsoon\tsid\tabc\n\
+                    \tFALSE\t/\tFALSE\t0\tsid\tabc\n\
+                    example.com\tFALSE\t/\tFALSE\t0\t\tabc\n\
+                    example.com\tFALSE\t/\tFALSE\t0\tkept\tyes\n";
+        let cookies = parse_cookies_txt(text);
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].name, "kept");
+    }
+
+    #[test]
+    fn cookie_target_keeps_leading_dot_domain() {
+        let (url, domain) = cookie_target(&cookie(".example.com", None, None));
+        assert_eq!(url, "https://example.com/");
+        assert_eq!(domain.as_deref(), Some(".example.com"));
+    }
+
+    #[test]
+    fn cookie_target_uses_exact_host_for_host_only_cookies() {
+        let (url, domain) = cookie_target(&cookie("https://Example.com:8443/login", None, None));
+        assert_eq!(url, "https://example.com/");
+        assert_eq!(domain, None);
+    }
+
+    #[test]
+    fn cookie_target_matches_subdomains() {
+        let (url, domain) = cookie_target(&cookie("app.example.com", Some("account"), Some(false)));
+        assert_eq!(url, "http://app.example.com/account");
+        assert_eq!(domain, None);
+
+        let (url, domain) = cookie_target(&cookie(".app.example.com", Some("/"), None));
+        assert_eq!(url, "https://app.example.com/");
+        assert_eq!(domain.as_deref(), Some(".app.example.com"));
+    }
+
+    struct RecordingNavigator {
+        location: std::sync::Mutex<String>,
+        visits: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl RecordingNavigator {
+        fn at(url: &str) -> Self {
+            Self {
+                location: std::sync::Mutex::new(url.to_string()),
+                visits: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+
+        fn land(&self, url: &str) {
+            *self.location.lock().unwrap() = url.to_string();
+        }
+
+        fn visits(&self) -> Vec<String> {
+            self.visits.lock().unwrap().clone()
+        }
+    }
+
+    impl Navigator for RecordingNavigator {
+        async fn location(&self) -> String {
+            self.location.lock().unwrap().clone()
+        }
+
+        async fn navigate_to(&self, url: &str) -> Result<(), ScrapeError> {
+            self.visits.lock().unwrap().push(url.to_string());
+            self.land(url);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn login_landing_on_target_navigates_once() {
+        let nav = RecordingNavigator::at("about:blank");
+        nav.navigate_to("https://example.com/login").await.unwrap();
+        nav.land("https://example.com/feed");
+
+        assert!(!land_on_target(&nav, "https://example.com/feed").await.unwrap());
+        assert!(!land_on_target(&nav, "https://example.com/feed/").await.unwrap());
+        assert_eq!(nav.visits(), vec!["https://example.com/login"]);
+    }
+
+    #[tokio::test]
+    async fn login_landing_elsewhere_navigates_to_target_once() {
+        let nav = RecordingNavigator::at("about:blank");
+        nav.navigate_to("https://example.com/login").await.unwrap();
+        nav.land("https://example.com/home");
+
+        assert!(land_on_target(&nav, "https://example.com/feed").await.unwrap());
+        assert!(!land_on_target(&nav, "https://example.com/feed").await.unwrap());
+        assert_eq!(nav.visits(), vec!["https://example.com/login", "https://example.com/feed"]);
+    }
+
+    #[test]
+    fn same_page_ignores_trailing_slash_and_fragment() {
+        assert!(is_same_page("https://example.com/feed/", "https://example.com/feed"));
+        assert!(is_same_page("https://example.com/feed", "https://example.com/feed/"));
+        assert!(is_same_page("https://example.com/feed#top", "https://example.com/feed"));
+    }
+
+    #[test]
+    fn same_page_ignores_scheme_and_host_case_but_not_path_case() {
+        assert!(is_same_page("HTTPS://Example.COM/Docs", "https://example.com/Docs"));
+        assert!(!is_same_page("https://example.com/docs", "https://example.com/Docs"));
+    }
+
+    #[test]
+    fn same_page_requires_the_same_query() {
+        assert!(is_same_page("https://example.com/search?q=a", "https://example.com/search?q=a"));
+        assert!(!is_same_page("https://example.com/feed?ref=login", "https://example.com/feed"));
+        assert!(!is_same_page("https://example.com/search?q=a&page=2", "https://example.com/search?q=a"));
+    }
+
+    #[test]
+    fn same_page_rejects_children_of_the_target() {
+        assert!(!is_same_page("https://example.com/login", "https://example.com/"));
+        assert!(!is_same_page("https://example.com/login", "https://example.com"));
+        assert!(!is_same_page("https://example.com/docs/intro", "https://example.com/docs"));
+        assert!(!is_same_page("https://example.com/feeds", "https://example.com/feed"));
+        assert!(!is_same_page("https://example.com.evil.test/", "https://example.com"));
+    }
+
+    #[test]
+    fn same_page_rejects_blank_current_url() {
+        assert!(!is_same_page("", "https://example.com"));
+        assert!(!is_same_page("https://example.com/login", "https://example.com/feed"));
+    }
+}
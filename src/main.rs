@@ -1,16 +1,34 @@
 use std::env;
-use actix_web::{web, App, HttpServer, middleware::Logger};
+use actix_web::{web, App, HttpServer, middleware::{from_fn, Logger}};
 use actix_files::Files;
 use env_logger::init;
 
+mod cdp;
+mod colors;
 mod errors;
 mod model;
 mod config;
+mod dates;
+mod interactions;
+mod language;
 mod login;
+mod metrics;
+mod middleware;
+mod pool;
+mod progress;
+mod ratelimit;
 mod scraper;
 mod handlers;
+mod jobs;
+mod sessions;
+mod shutdown;
+mod sitemap;
+mod webhook;
 
-use handlers::{health, scrape};
+use handlers::{
+    cdp, create_job, delete_session, export_metrics, get_job, health, login_multi, render, scrape,
+    scrape_get, scrape_live,
+};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -18,15 +36,30 @@ async fn main() -> std::io::Result<()> {
     let port_str = env::var("PORT").unwrap_or_else(|_| "8000".to_string());
     let port = port_str.parse::<u16>().expect("PORT must be a valid number");
     let bind_address = format!("0.0.0.0:{}", port);
+    tokio::spawn(pool::warm_up());
     let server = HttpServer::new(|| {
         App::new()
+            .wrap(from_fn(middleware::enforce_timeout))
+            .wrap(from_fn(ratelimit::limit_by_ip))
+            .wrap(from_fn(middleware::require_api_key))
             .wrap(Logger::default())
             .route("/health", web::get().to(health))
+            .route("/metrics", web::get().to(export_metrics))
             .route("/scrape", web::post().to(scrape))
+            .route("/scrape", web::get().to(scrape_get))
+            .route("/scrape/live", web::post().to(scrape_live))
+            .route("/render", web::post().to(render))
+            .route("/sessions/{id}", web::delete().to(delete_session))
+            .route("/login/multi", web::post().to(login_multi))
+            .route("/cdp", web::post().to(cdp))
+            .route("/jobs", web::post().to(create_job))
+            .route("/jobs/{id}", web::get().to(get_job))
             .service(Files::new("/", "./static").index_file("index.html"))
     })
+    .shutdown_timeout(shutdown::shutdown_timeout().as_secs())
     .bind(bind_address)?;
     
     server.run().await?;
+    shutdown::close_all().await;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,75 @@
+use crate::errors::ScrapeError;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+struct Metrics {
+    registry: Registry,
+    scrapes: IntCounter,
+    successes: IntCounter,
+    failures: IntCounterVec,
+    login_attempts: IntCounter,
+    login_successes: IntCounter,
+    duration: HistogramVec,
+}
+
+fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| {
+        let registry = Registry::new();
+        let scrapes = IntCounter::new("scrapes_total", "Total scrape requests").unwrap();
+        let successes = IntCounter::new("scrape_successes_total", "Successful scrapes").unwrap();
+        let failures = IntCounterVec::new(
+            Opts::new("scrape_failures_total", "Failed scrapes by error kind"),
+            &["error"],
+        )
+        .unwrap();
+        let login_attempts = IntCounter::new("login_attempts_total", "Automatic login attempts").unwrap();
+        let login_successes = IntCounter::new("login_successes_total", "Successful automatic logins").unwrap();
+        let duration = HistogramVec::new(
+            HistogramOpts::new("scrape_duration_seconds", "Scrape duration in seconds")
+                .buckets(vec![0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 300.0]),
+            &["login"],
+        )
+        .unwrap();
+
+        registry.register(Box::new(scrapes.clone())).unwrap();
+        registry.register(Box::new(successes.clone())).unwrap();
+        registry.register(Box::new(failures.clone())).unwrap();
+        registry.register(Box::new(login_attempts.clone())).unwrap();
+        registry.register(Box::new(login_successes.clone())).unwrap();
+        registry.register(Box::new(duration.clone())).unwrap();
+
+        Metrics { registry, scrapes, successes, failures, login_attempts, login_successes, duration }
+    })
+}
+
+pub fn record_scrape<T>(result: &Result<T, ScrapeError>, login_attempted: bool, elapsed: Duration) {
+    let m = metrics();
+    m.scrapes.inc();
+    match result {
+        Ok(_) => m.successes.inc(),
+        Err(e) => m.failures.with_label_values(&[e.kind()]).inc(),
+    }
+    m.duration
+        .with_label_values(&[if login_attempted { "true" } else { "false" }])
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn record_login(success: bool) {
+    let m = metrics();
+    m.login_attempts.inc();
+    if success {
+        m.login_successes.inc();
+    }
+}
+
+pub fn render() -> Result<String, String> {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&metrics().registry.gather(), &mut buffer)
+        .map_err(|e| e.to_string())?;
+    String::from_utf8(buffer).map_err(|e| e.to_string())
+}
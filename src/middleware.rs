@@ -0,0 +1,89 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::http::header;
+use actix_web::{Error, HttpResponse};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::warn;
+
+const PROTECTED_PREFIXES: &[&str] = &["/scrape", "/render", "/sessions", "/login", "/cdp", "/jobs"];
+
+fn api_keys() -> &'static [String] {
+    static KEYS: OnceLock<Vec<String>> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        std::env::var("API_KEYS")
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    })
+}
+
+pub(crate) fn is_protected(path: &str) -> bool {
+    PROTECTED_PREFIXES.iter().any(|prefix| {
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+pub async fn require_api_key(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let keys = api_keys();
+    if keys.is_empty() || !is_protected(req.path()) {
+        return next.call(req).await.map(ServiceResponse::map_into_boxed_body);
+    }
+
+    let authorized = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|key| keys.iter().any(|k| k == key.trim()));
+    if authorized {
+        return next.call(req).await.map(ServiceResponse::map_into_boxed_body);
+    }
+
+    warn!("Rejected unauthenticated request to {}", req.path());
+    let response = HttpResponse::Unauthorized()
+        .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+        .body("Missing or invalid API key");
+    Ok(req.into_response(response))
+}
+
+fn request_timeout() -> Option<Duration> {
+    static TIMEOUT: OnceLock<Option<Duration>> = OnceLock::new();
+    *TIMEOUT.get_or_init(|| {
+        let secs = std::env::var("REQUEST_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    })
+}
+
+pub async fn enforce_timeout(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let Some(limit) = request_timeout() else {
+        return next.call(req).await.map(ServiceResponse::map_into_boxed_body);
+    };
+
+    let http_req = req.request().clone();
+    match tokio::time::timeout(limit, next.call(req)).await {
+        Ok(response) => response.map(ServiceResponse::map_into_boxed_body),
+        Err(_) => {
+            warn!("Request to {} exceeded {}s", http_req.path(), limit.as_secs());
+            let response = HttpResponse::GatewayTimeout()
+                .body(format!("Request timed out after {}s", limit.as_secs()));
+            Ok(ServiceResponse::new(http_req, response))
+        }
+    }
+}
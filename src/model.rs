@@ -1,11 +1,548 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Default)]
 pub struct ScrapeRequest {
+    #[serde(default)]
     pub url: String,
+    #[serde(default)]
+    pub urls: Option<Vec<String>>,
     
     #[serde(default)]
     pub login: Option<LoginCredentials>,
+
+    #[serde(default)]
+    pub extract_landmarks: bool,
+
+    #[serde(default)]
+    pub csv_collection: Option<String>,
+
+    #[serde(default)]
+    pub connect_url: Option<String>,
+
+    #[serde(default)]
+    pub detect_hidden_text: bool,
+
+    #[serde(default)]
+    pub ignore_https_errors: bool,
+
+    #[serde(default)]
+    pub capture_xhr: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub scroll_until: Option<ScrollUntil>,
+
+    #[serde(default)]
+    pub selectors: Option<Vec<SelectorRule>>,
+
+    #[serde(default)]
+    pub search: Option<SearchAction>,
+
+    #[serde(default)]
+    pub text_only: bool,
+
+    #[serde(default)]
+    pub evasions: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub spa_route: bool,
+
+    #[serde(default)]
+    pub session_id: Option<String>,
+
+    #[serde(default)]
+    pub extract_form_state: Option<String>,
+
+    #[serde(default)]
+    pub javascript: Option<bool>,
+
+    #[serde(default)]
+    pub capture_set_cookies: bool,
+
+    #[serde(default)]
+    pub max_dom_nodes: Option<usize>,
+
+    #[serde(default)]
+    pub include_noscript: bool,
+
+    #[serde(default)]
+    pub extract_faqs: bool,
+
+    #[serde(default)]
+    pub max_redirects: Option<usize>,
+
+    #[serde(default)]
+    pub extract_dates: bool,
+
+    #[serde(default)]
+    pub sanitize_html: bool,
+
+    #[serde(default)]
+    pub detect_framework: bool,
+
+    #[serde(default)]
+    pub semantic_sections: bool,
+
+    #[serde(default)]
+    pub scroll_strategy: Option<ScrollStrategy>,
+
+    #[serde(default)]
+    pub include_screenshot: bool,
+
+    #[serde(default)]
+    pub report_blocked_requests: bool,
+
+    #[serde(default)]
+    pub referer: Option<String>,
+
+    #[serde(default)]
+    pub check_anchors: bool,
+
+    #[serde(default)]
+    pub field_aliases: Option<HashMap<String, String>>,
+
+    #[serde(default)]
+    pub seen_hrefs: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub wait_for_dom_stable: Option<DomStableWait>,
+
+    #[serde(default)]
+    pub computed_styles: Option<ComputedStylesRequest>,
+
+    #[serde(default)]
+    pub follow_redirects: bool,
+
+    #[serde(default)]
+    pub resource_limits: Option<ResourceLimits>,
+
+    #[serde(default)]
+    pub product_selector: Option<String>,
+
+    #[serde(default)]
+    pub product_fields: Option<ProductFields>,
+
+    #[serde(default)]
+    pub warmup_url: Option<String>,
+
+    #[serde(default)]
+    pub detect_cmp: bool,
+
+    #[serde(default)]
+    pub capture_title_history: bool,
+
+    #[serde(default)]
+    pub text_stats: bool,
+
+    #[serde(default)]
+    pub bypass_cache: bool,
+
+    #[serde(default)]
+    pub strip_tracking_params: bool,
+
+    #[serde(default)]
+    pub extra_tracking_params: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub follow_new_tabs: bool,
+
+    #[serde(default)]
+    pub empty_content_retry: bool,
+
+    #[serde(default)]
+    pub min_text_length: Option<usize>,
+
+    #[serde(default)]
+    pub envelope: bool,
+
+    #[serde(default)]
+    pub min_image_width: Option<u32>,
+
+    #[serde(default)]
+    pub min_image_height: Option<u32>,
+
+    #[serde(default)]
+    pub extract_colors: bool,
+
+    #[serde(default)]
+    pub proxy: Option<ProxySetting>,
+
+    #[serde(default)]
+    pub proxy_pool: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub wait_for_text: Option<TextWait>,
+
+    #[serde(default)]
+    pub pdf: bool,
+
+    #[serde(default)]
+    pub pdf_options: Option<PdfOptions>,
+
+    #[serde(default, deserialize_with = "lowercase_list")]
+    pub block_resources: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub extract: Option<HashMap<String, String>>,
+
+    #[serde(default)]
+    pub extract_tables: bool,
+
+    #[serde(default)]
+    pub return_cookies: bool,
+
+    #[serde(default)]
+    pub timeouts: Option<TimeoutConfig>,
+
+    #[serde(default)]
+    pub wait_for_selector: Option<String>,
+
+    #[serde(default)]
+    pub output_format: Option<OutputFormat>,
+
+    #[serde(default)]
+    pub user_agent: Option<String>,
+
+    #[serde(default)]
+    pub device: Option<String>,
+
+    #[serde(default)]
+    pub callback_url: Option<String>,
+
+    #[serde(default)]
+    pub crawl: Option<CrawlOptions>,
+
+    #[serde(default)]
+    pub from_sitemap: Option<String>,
+
+    #[serde(default)]
+    pub scroll: Option<ScrollConfig>,
+
+    #[serde(default)]
+    pub pre_extract_js: Option<String>,
+
+    #[serde(default)]
+    pub actions: Option<Vec<Action>>,
+
+    #[serde(default)]
+    pub screenshot_selector: Option<String>,
+
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+
+    #[serde(default)]
+    pub detect_language: bool,
+
+    #[serde(default)]
+    pub hash_html: bool,
+
+    #[serde(default)]
+    pub if_none_match: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SearchAction {
+    pub selector: String,
+    pub query: String,
+    #[serde(default)]
+    pub submit_selector: Option<String>,
+    #[serde(default)]
+    pub follow_new_tab: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TextMode {
+    #[default]
+    InnerText,
+    TextContent,
+    InnerHtml,
+    OuterHtml,
+}
+
+impl TextMode {
+    pub fn js_property(&self) -> &'static str {
+        match self {
+            TextMode::InnerText => "innerText",
+            TextMode::TextContent => "textContent",
+            TextMode::InnerHtml => "innerHTML",
+            TextMode::OuterHtml => "outerHTML",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Markdown,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct TimeoutConfig {
+    #[serde(default)]
+    pub navigation_ms: Option<u64>,
+    #[serde(default)]
+    pub body_wait_ms: Option<u64>,
+    #[serde(default)]
+    pub idle_ms: Option<u64>,
+}
+
+impl TimeoutConfig {
+    pub const DEFAULT_NAVIGATION_MS: u64 = 30000;
+    pub const DEFAULT_BODY_WAIT_MS: u64 = 10000;
+    pub const DEFAULT_IDLE_MS: u64 = 2000;
+
+    pub fn navigation(&self) -> Duration {
+        Duration::from_millis(self.navigation_ms.unwrap_or(Self::DEFAULT_NAVIGATION_MS))
+    }
+
+    pub fn body_wait(&self) -> Duration {
+        Duration::from_millis(self.body_wait_ms.unwrap_or(Self::DEFAULT_BODY_WAIT_MS))
+    }
+
+    pub fn idle(&self) -> Duration {
+        Duration::from_millis(self.idle_ms.unwrap_or(Self::DEFAULT_IDLE_MS))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SelectorRule {
+    pub name: String,
+    pub selector: String,
+    #[serde(default)]
+    pub text_mode: TextMode,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollStrategy {
+    None,
+    #[default]
+    Fixed,
+    Adaptive,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ComputedStylesRequest {
+    pub selector: String,
+    pub properties: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ResourceLimits {
+    #[serde(default)]
+    pub max_heap_mb: Option<u32>,
+    #[serde(default)]
+    pub max_renderer_processes: Option<u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ProductFields {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub price: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DomStableWait {
+    #[serde(default)]
+    pub quiet_ms: Option<u64>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProxyConfig {
+    #[serde(default)]
+    pub scheme: Option<String>,
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl ProxyConfig {
+    pub fn parse(url: &str) -> Option<Self> {
+        let (scheme, rest) = url.split_once("://")?;
+        let (userinfo, address) = match rest.trim_end_matches('/').rsplit_once('@') {
+            Some((userinfo, address)) => (Some(userinfo), address),
+            None => (None, rest.trim_end_matches('/')),
+        };
+        let (host, port) = address.rsplit_once(':')?;
+        let (username, password) = match userinfo.map(|u| u.split_once(':').unwrap_or((u, ""))) {
+            Some((user, pass)) => (Some(user.to_string()), Some(pass.to_string())),
+            None => (None, None),
+        };
+        Some(Self {
+            scheme: Some(scheme.to_string()),
+            host: host.to_string(),
+            port: port.parse().ok()?,
+            username,
+            password,
+        })
+    }
+
+    pub fn scheme(&self) -> &str {
+        self.scheme.as_deref().unwrap_or("http")
+    }
+
+    pub fn server(&self) -> String {
+        format!("{}://{}:{}", self.scheme(), self.host, self.port)
+    }
+
+    pub fn credentials(&self) -> Option<(String, String)> {
+        let username = self.username.clone()?;
+        Some((username, self.password.clone().unwrap_or_default()))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ProxySetting {
+    Url(String),
+    Config(ProxyConfig),
+}
+
+impl ProxySetting {
+    pub fn resolve(&self) -> Option<ProxyConfig> {
+        match self {
+            ProxySetting::Url(url) => ProxyConfig::parse(url),
+            ProxySetting::Config(config) => Some(config.clone()),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PdfOptions {
+    #[serde(default)]
+    pub landscape: bool,
+    #[serde(default)]
+    pub print_background: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TextWait {
+    pub selector: String,
+    #[serde(default)]
+    pub contains: Option<String>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    Click {
+        selector: String,
+    },
+    Type {
+        selector: String,
+        text: String,
+    },
+    WaitForSelector {
+        selector: String,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+    },
+    WaitMs {
+        ms: u64,
+    },
+    Scroll {
+        #[serde(default)]
+        selector: Option<String>,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct ScrollConfig {
+    #[serde(default)]
+    pub max_scrolls: Option<usize>,
+    #[serde(default)]
+    pub delay_ms: Option<u64>,
+    #[serde(default)]
+    pub stop_when_no_new_content: Option<bool>,
+    #[serde(default)]
+    pub container: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScrollUntil {
+    pub selector: String,
+    pub min_count: usize,
+    #[serde(default)]
+    pub max_scrolls: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CrawlOptions {
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+    #[serde(default)]
+    pub max_pages: Option<usize>,
+    #[serde(default)]
+    pub same_domain_only: Option<bool>,
+    #[serde(default)]
+    pub url_filter: Option<String>,
+    #[serde(default)]
+    pub exclude_filter: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScrapeQuery {
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScrapeGetQuery {
+    pub url: String,
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub csv_collection: Option<String>,
+    #[serde(default)]
+    pub text_only: bool,
+    #[serde(default)]
+    pub extract_landmarks: bool,
+    #[serde(default)]
+    pub detect_hidden_text: bool,
+    #[serde(default)]
+    pub ignore_https_errors: bool,
+}
+
+fn lowercase_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<String>>, D::Error> {
+    let list = Option::<Vec<String>>::deserialize(deserializer)?;
+    Ok(list.map(|items| items.iter().map(|item| item.trim().to_ascii_lowercase()).collect()))
+}
+
+impl ScrapeRequest {
+    pub fn javascript_enabled(&self) -> bool {
+        self.javascript.unwrap_or(true)
+    }
+}
+
+impl From<ScrapeGetQuery> for ScrapeRequest {
+    fn from(query: ScrapeGetQuery) -> Self {
+        ScrapeRequest {
+            url: query.url,
+            csv_collection: query.csv_collection,
+            text_only: query.text_only,
+            extract_landmarks: query.extract_landmarks,
+            detect_hidden_text: query.detect_hidden_text,
+            ignore_https_errors: query.ignore_https_errors,
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -15,6 +552,8 @@ pub struct LoginCredentials {
     
     #[serde(default)]
     pub platform: Option<String>,
+    #[serde(default)]
+    pub platform_candidates: Option<Vec<String>>,
     
     #[serde(default)]
     pub login_url: Option<String>,
@@ -29,6 +568,18 @@ pub struct LoginCredentials {
     pub wait_after_login_secs: Option<u64>,
     #[serde(default)]
     pub cookies: Option<Vec<CookieData>>,
+    #[serde(default)]
+    pub cookies_txt: Option<String>,
+    #[serde(default)]
+    pub success_check_js: Option<String>,
+    #[serde(default)]
+    pub error_check_js: Option<String>,
+    #[serde(default)]
+    pub login_in_iframe: bool,
+    #[serde(default)]
+    pub next_selector: Option<String>,
+    #[serde(default)]
+    pub totp_secret: Option<String>,
 }
 
 #[derive(Deserialize, Clone, Serialize, Debug)] 
@@ -37,18 +588,143 @@ pub struct CookieData {
     pub value: String,
     pub domain: String,
     pub path: Option<String>,
+    #[serde(default)]
+    pub secure: Option<bool>,
+    #[serde(default, alias = "httpOnly")]
+    pub http_only: Option<bool>,
+    #[serde(default, alias = "sameSite")]
+    pub same_site: Option<String>,
+    #[serde(default)]
+    pub expires: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ImageData {
     pub src: String,
     pub alt: String,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct LinkData {
     pub href: String,
     pub text: String,
+    #[serde(default)]
+    pub is_internal: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LandmarkData {
+    pub role: String,
+    pub tag: String,
+    pub text_length: usize,
+    pub selector: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct XhrResponseData {
+    pub url: String,
+    pub status: i64,
+    pub body: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SelectorMatch {
+    pub text: String,
+    pub html: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TableData {
+    pub index: usize,
+    pub caption: Option<String>,
+    pub headers: Vec<String>,
+    pub rows: Vec<HashMap<String, String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FaqEntry {
+    pub question: String,
+    pub answer: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CdpRequest {
+    pub method: String,
+    #[serde(default)]
+    pub params: Option<serde_json::Value>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MultiLoginRequest {
+    pub logins: Vec<LoginCredentials>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PlatformLoginResult {
+    pub platform: Option<String>,
+    pub success: bool,
+    pub requires_2fa: Option<bool>,
+    pub error: Option<String>,
+    pub cookies: Vec<CookieData>,
+    pub login_frame: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MultiLoginResponse {
+    pub results: Vec<PlatformLoginResult>,
+    pub cookies: Vec<CookieData>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DateCandidate {
+    pub raw: String,
+    pub source: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DateData {
+    pub raw: String,
+    pub normalized: String,
+    pub source: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TextStats {
+    pub char_count: usize,
+    pub emoji_count: usize,
+    pub uppercase_ratio: f64,
+    pub digit_ratio: f64,
+    pub dominant_block: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SectionData {
+    pub tag: String,
+    pub text: String,
+    pub link_count: usize,
+    pub image_count: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BlockedRequest {
+    pub url: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Product {
+    pub title: Option<String>,
+    pub price: Option<String>,
+    pub image: Option<String>,
+    pub url: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -65,6 +741,57 @@ pub struct ScrapeResponse {
     pub login_success: Option<bool>,
     pub platform_detected: Option<String>,
     pub requires_2fa: Option<bool>,
+    pub landmarks: Option<Vec<LandmarkData>>,
+    pub hidden_text: Option<String>,
+    pub xhr_responses: Option<Vec<XhrResponseData>>,
+    pub matched_count: Option<usize>,
+    pub selector_results: Option<HashMap<String, Vec<SelectorMatch>>>,
+    pub text_direction: Option<String>,
+    pub script: Option<String>,
+    pub extraction_warnings: Vec<String>,
+    pub session_refreshed: bool,
+    pub form_state: Option<HashMap<String, String>>,
+    pub cookies_set: Option<Vec<CookieData>>,
+    pub noscript_content: Option<Vec<String>>,
+    pub faqs: Option<Vec<FaqEntry>>,
+    pub redirect_chain: Vec<String>,
+    pub dates: Option<Vec<DateData>>,
+    pub framework: Option<String>,
+    pub sections: Option<Vec<SectionData>>,
+    pub screenshot: Option<String>,
+    pub blocked_requests: Option<Vec<BlockedRequest>>,
+    pub broken_anchors: Option<Vec<String>>,
+    pub new_links: Option<Vec<LinkData>>,
+    pub computed_styles: Option<Vec<HashMap<String, String>>>,
+    pub products: Option<Vec<Product>>,
+    pub warmup_performed: bool,
+    pub cmp: Option<String>,
+    pub login_frame: Option<String>,
+    pub title_history: Option<Vec<String>>,
+    pub text_stats: Option<TextStats>,
+    pub followed_tab: Option<String>,
+    pub extraction_retried: bool,
+    pub dominant_color: Option<String>,
+    pub palette: Option<Vec<String>>,
+    pub proxy_used: Option<String>,
+    pub pdf: Option<String>,
+    pub metadata: HashMap<String, String>,
+    pub structured_data: Vec<serde_json::Value>,
+    pub extracted: HashMap<String, Vec<String>>,
+    pub tables: Vec<TableData>,
+    pub cookies: Option<Vec<CookieData>>,
+    pub script_result: Option<serde_json::Value>,
+    pub language: Option<String>,
+    pub language_confidence: Option<f64>,
+    pub content_hash: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RenderResponse {
+    pub url: String,
+    pub success: bool,
+    pub html: Option<String>,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -78,4 +805,47 @@ pub struct ScrapedData {
     pub login_success: Option<bool>,
     pub platform_detected: Option<String>,
     pub requires_2fa: Option<bool>,
+    pub landmarks: Option<Vec<LandmarkData>>,
+    pub hidden_text: Option<String>,
+    pub xhr_responses: Option<Vec<XhrResponseData>>,
+    pub matched_count: Option<usize>,
+    pub selector_results: Option<HashMap<String, Vec<SelectorMatch>>>,
+    pub text_direction: Option<String>,
+    pub script: Option<String>,
+    pub extraction_warnings: Vec<String>,
+    pub session_refreshed: bool,
+    pub form_state: Option<HashMap<String, String>>,
+    pub cookies_set: Option<Vec<CookieData>>,
+    pub noscript_content: Option<Vec<String>>,
+    pub faqs: Option<Vec<FaqEntry>>,
+    pub redirect_chain: Vec<String>,
+    pub dates: Option<Vec<DateData>>,
+    pub framework: Option<String>,
+    pub sections: Option<Vec<SectionData>>,
+    pub screenshot: Option<String>,
+    pub blocked_requests: Option<Vec<BlockedRequest>>,
+    pub broken_anchors: Option<Vec<String>>,
+    pub new_links: Option<Vec<LinkData>>,
+    pub computed_styles: Option<Vec<HashMap<String, String>>>,
+    pub products: Option<Vec<Product>>,
+    pub warmup_performed: bool,
+    pub cmp: Option<String>,
+    pub login_frame: Option<String>,
+    pub title_history: Option<Vec<String>>,
+    pub text_stats: Option<TextStats>,
+    pub followed_tab: Option<String>,
+    pub extraction_retried: bool,
+    pub dominant_color: Option<String>,
+    pub palette: Option<Vec<String>>,
+    pub proxy_used: Option<String>,
+    pub pdf: Option<String>,
+    pub metadata: HashMap<String, String>,
+    pub structured_data: Vec<serde_json::Value>,
+    pub extracted: HashMap<String, Vec<String>>,
+    pub tables: Vec<TableData>,
+    pub cookies: Option<Vec<CookieData>>,
+    pub script_result: Option<serde_json::Value>,
+    pub language: Option<String>,
+    pub language_confidence: Option<f64>,
+    pub content_hash: String,
 }
@@ -0,0 +1,141 @@
+use crate::errors::ScrapeError;
+use crate::scraper::{BrowserSlot, LaunchOptions, launch_browser};
+use crate::shutdown;
+use chromiumoxide::browser::Browser;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task;
+use tracing::{info, warn};
+
+struct IdleBrowser {
+    browser: Browser,
+    handler: task::JoinHandle<()>,
+    slot: BrowserSlot,
+}
+
+pub struct PoolLease {
+    slot: BrowserSlot,
+    permit: OwnedSemaphorePermit,
+}
+
+pub struct PooledBrowser {
+    pub browser: Browser,
+    pub handler: task::JoinHandle<()>,
+    pub lease: PoolLease,
+}
+
+struct BrowserPool {
+    idle: Mutex<Vec<IdleBrowser>>,
+    permits: Arc<Semaphore>,
+}
+
+fn pool_size() -> usize {
+    static SIZE: OnceLock<usize> = OnceLock::new();
+    *SIZE.get_or_init(|| {
+        std::env::var("BROWSER_POOL_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(2)
+    })
+}
+
+fn pool() -> &'static BrowserPool {
+    static POOL: OnceLock<BrowserPool> = OnceLock::new();
+    POOL.get_or_init(|| BrowserPool {
+        idle: Mutex::new(Vec::new()),
+        permits: Arc::new(Semaphore::new(pool_size())),
+    })
+}
+
+async fn launch() -> Result<IdleBrowser, ScrapeError> {
+    let slot = BrowserSlot::acquire()?;
+    let options = LaunchOptions { headless: true, ..Default::default() };
+    let (browser, handler) = launch_browser(&options).await?;
+    Ok(IdleBrowser { browser, handler, slot })
+}
+
+fn push_idle(browser: IdleBrowser) {
+    if let Ok(mut idle) = pool().idle.lock() {
+        idle.push(browser);
+    }
+}
+
+async fn discard(mut browser: Browser, handler: task::JoinHandle<()>) {
+    let _ = browser.close().await;
+    let _ = browser.wait().await;
+    shutdown::unregister(browser.websocket_address());
+    handler.abort();
+}
+
+pub async fn warm_up() {
+    let size = pool_size();
+    for launched in 0..size {
+        match launch().await {
+            Ok(browser) => push_idle(browser),
+            Err(e) => {
+                warn!("Browser pool warm-up stopped at {}/{}: {}", launched, size, e);
+                return;
+            }
+        }
+    }
+    info!("Browser pool ready with {} browsers", size);
+}
+
+pub async fn checkout() -> Result<PooledBrowser, ScrapeError> {
+    let permit = pool()
+        .permits
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|_| ScrapeError::BrowserLaunch("browser pool closed".to_string()))?;
+
+    let idle = loop {
+        let candidate = pool().idle.lock().ok().and_then(|mut idle| idle.pop());
+        let (mut browser, reused) = match candidate {
+            Some(browser) if browser.handler.is_finished() => {
+                warn!("Discarding crashed idle browser");
+                discard(browser.browser, browser.handler).await;
+                continue;
+            }
+            Some(browser) => (browser, true),
+            None => (launch().await?, false),
+        };
+        match browser.browser.start_incognito_context().await {
+            Ok(_) => break browser,
+            Err(e) => {
+                warn!("Discarding browser that could not open a fresh context: {}", e);
+                discard(browser.browser, browser.handler).await;
+                if !reused {
+                    return Err(ScrapeError::BrowserLaunch(format!("browser context: {}", e)));
+                }
+            }
+        }
+    };
+
+    Ok(PooledBrowser {
+        browser: idle.browser,
+        handler: idle.handler,
+        lease: PoolLease { slot: idle.slot, permit },
+    })
+}
+
+pub async fn checkin(mut browser: Browser, handler: task::JoinHandle<()>, lease: PoolLease) {
+    let PoolLease { slot, permit } = lease;
+    let healthy = !handler.is_finished()
+        && browser.quit_incognito_context().await.is_ok();
+    if healthy {
+        push_idle(IdleBrowser { browser, handler, slot });
+        drop(permit);
+        return;
+    }
+
+    warn!("Pooled browser crashed, replacing it");
+    discard(browser, handler).await;
+    drop(slot);
+    match launch().await {
+        Ok(replacement) => push_idle(replacement),
+        Err(e) => warn!("Failed to replace pooled browser: {}", e),
+    }
+    drop(permit);
+}
@@ -0,0 +1,31 @@
+use futures::channel::mpsc::UnboundedSender;
+use std::future::Future;
+use tracing::info;
+
+tokio::task_local! {
+    static PROGRESS: UnboundedSender<String>;
+}
+
+pub async fn with_progress<F: Future>(sender: UnboundedSender<String>, future: F) -> F::Output {
+    PROGRESS.scope(sender, future).await
+}
+
+pub fn emit(event: &str, message: impl Into<String>) {
+    let message = message.into();
+    info!("[{}] {}", event, message);
+    let _ = PROGRESS.try_with(|sender| sender.unbounded_send(sse_frame(event, &message)));
+}
+
+pub fn sse_frame(event: &str, data: &str) -> String {
+    let mut frame = format!("event: {}\n", event);
+    if data.is_empty() {
+        frame.push_str("data: \n");
+    }
+    for line in data.lines() {
+        frame.push_str("data: ");
+        frame.push_str(line);
+        frame.push('\n');
+    }
+    frame.push('\n');
+    frame
+}
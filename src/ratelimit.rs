@@ -0,0 +1,102 @@
+use crate::middleware::is_protected;
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header;
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpResponse};
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+fn requests_per_minute() -> usize {
+    static RPM: OnceLock<usize> = OnceLock::new();
+    *RPM.get_or_init(|| {
+        std::env::var("RATE_LIMIT_RPM")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0)
+    })
+}
+
+fn trusted_proxies() -> &'static [IpAddr] {
+    static PROXIES: OnceLock<Vec<IpAddr>> = OnceLock::new();
+    PROXIES.get_or_init(|| {
+        std::env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| match entry.parse() {
+                Ok(ip) => Some(ip),
+                Err(_) => {
+                    warn!("Ignoring invalid TRUSTED_PROXIES entry: {}", entry);
+                    None
+                }
+            })
+            .collect()
+    })
+}
+
+fn client_key(req: &ServiceRequest) -> String {
+    let Some(peer) = req.peer_addr().map(|addr| addr.ip()) else {
+        return "unknown".to_string();
+    };
+    if trusted_proxies().contains(&peer)
+        && let Some(forwarded) = req.connection_info().realip_remote_addr()
+    {
+        return forwarded.to_string();
+    }
+    peer.to_string()
+}
+
+fn windows() -> &'static Mutex<HashMap<String, VecDeque<Instant>>> {
+    static WINDOWS: OnceLock<Mutex<HashMap<String, VecDeque<Instant>>>> = OnceLock::new();
+    WINDOWS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn check(client: &str, limit: usize) -> Result<(), Duration> {
+    let now = Instant::now();
+    let Ok(mut windows) = windows().lock() else {
+        return Ok(());
+    };
+    if windows.len() > 10_000 {
+        windows.retain(|_, hits| hits.back().is_some_and(|last| now.duration_since(*last) < WINDOW));
+    }
+
+    let hits = windows.entry(client.to_string()).or_default();
+    while hits.front().is_some_and(|first| now.duration_since(*first) >= WINDOW) {
+        hits.pop_front();
+    }
+    if hits.len() >= limit {
+        let oldest = hits.front().copied().unwrap_or(now);
+        return Err(WINDOW.saturating_sub(now.duration_since(oldest)));
+    }
+    hits.push_back(now);
+    Ok(())
+}
+
+pub async fn limit_by_ip(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let limit = requests_per_minute();
+    if limit == 0 || !is_protected(req.path()) {
+        return next.call(req).await.map(ServiceResponse::map_into_boxed_body);
+    }
+
+    let client = client_key(&req);
+    match check(&client, limit) {
+        Ok(()) => next.call(req).await.map(ServiceResponse::map_into_boxed_body),
+        Err(retry_after) => {
+            warn!("Rate limit exceeded for {}", client);
+            let response = HttpResponse::TooManyRequests()
+                .insert_header((header::RETRY_AFTER, retry_after.as_secs().max(1).to_string()))
+                .body(format!("Rate limit of {} requests per minute exceeded", limit));
+            Ok(req.into_response(response))
+        }
+    }
+}
@@ -1,272 +1,2919 @@
 
 
+use crate::cdp::RawCommand;
+use crate::colors::{fetch_image, palette};
+use crate::config::get_platform_config;
+use crate::dates::normalize_dates;
 use crate::errors::ScrapeError;
-use crate::login::auto_login;
-use crate::model::{ImageData, LinkData, LoginCredentials, ScrapedData};
+use crate::interactions::{click_element, press_enter, type_into_field, wait_for_any_element};
+use crate::language::detect_language;
+use crate::login::{
+    auto_login, check_authenticated, get_all_cookies, get_cookies, land_on_target, Navigator,
+};
+use crate::metrics;
+use crate::model::{
+    Action, BlockedRequest, ComputedStylesRequest, CookieData, CrawlOptions, DateCandidate,
+    DateData, DomStableWait, FaqEntry, ImageData, LandmarkData, LinkData, LoginCredentials,
+    MultiLoginRequest, MultiLoginResponse, OutputFormat, PdfOptions, PlatformLoginResult, Product,
+    ProductFields, ProxyConfig, ResourceLimits, ScrapedData, ScrapeRequest, ScrollConfig,
+    ScrollStrategy, ScrollUntil, SearchAction, SectionData, SelectorMatch, SelectorRule, TableData,
+    TextStats, TextWait, TimeoutConfig, XhrResponseData,
+};
+use crate::pool::{self, PoolLease, PooledBrowser};
+use crate::progress::emit;
+use crate::sessions::scrape_in_session;
+use crate::shutdown;
+use std::collections::{HashMap, HashSet, VecDeque};
 use chromiumoxide::browser::{Browser, BrowserConfig, HeadlessMode};
-use chromiumoxide::page::Page;
+use chromiumoxide::handler::Handler;
+use chromiumoxide::page::{Page, ScreenshotParams};
 use chromiumoxide::cdp::browser_protocol::emulation::{
-    SetDeviceMetricsOverrideParams, SetUserAgentOverrideParams,
+    SetDeviceMetricsOverrideParams, SetScriptExecutionDisabledParams, SetTouchEmulationEnabledParams,
+    SetUserAgentOverrideParams,
+};
+use chromiumoxide::cdp::browser_protocol::page::{
+    AddScriptToEvaluateOnNewDocumentParams, CaptureScreenshotFormat, EventDomContentEventFired,
+    NavigateParams, PrintToPdfParams, RemoveScriptToEvaluateOnNewDocumentParams, ScriptIdentifier,
+    Viewport,
+};
+use chromiumoxide::cdp::browser_protocol::network::{
+    ErrorReason, EventLoadingFailed, EventRequestWillBeSent, EventResponseReceived,
+    GetResponseBodyParams, RequestId, ResourceType, SetCacheDisabledParams,
 };
-use chromiumoxide::cdp::browser_protocol::page::AddScriptToEvaluateOnNewDocumentParams;
+use chromiumoxide::cdp::browser_protocol::fetch::{
+    AuthChallengeResponse, AuthChallengeResponseResponse, ContinueRequestParams,
+    ContinueWithAuthParams, DisableParams as FetchDisableParams, EnableParams as FetchEnableParams,
+    EventAuthRequired, EventRequestPaused, FailRequestParams,
+};
+use chromiumoxide::cdp::browser_protocol::security::SetIgnoreCertificateErrorsParams;
+use chromiumoxide::cdp::browser_protocol::target::{EventTargetCreated, TargetId};
+use chromiumoxide::cdp::js_protocol::runtime::EvaluateParams;
+use chromiumoxide::js::Evaluation;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use futures::StreamExt;
+use futures::future::join_all;
+use rand::Rng;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
 use tokio::task;
 
+const MAX_XHR_CAPTURE_BYTES: usize = 5 * 1024 * 1024;
+const DEFAULT_MAX_REDIRECTS: usize = 20;
+const TRACKING_PARAM_PREFIXES: &[&str] = &["utm_", "pk_", "mtm_", "hsa_"];
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "gclsrc", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "twclid",
+    "igshid", "mc_cid", "mc_eid", "_ga", "_gl", "_hsenc", "_hsmi", "mkt_tok", "oly_anon_id",
+    "oly_enc_id", "vero_id", "rb_clickid", "s_cid",
+];
+
+const BLOCKABLE_RESOURCES: &[&str] = &[
+    "image", "media", "font", "stylesheet", "script", "texttrack", "xhr", "fetch", "eventsource",
+    "websocket", "manifest", "ping", "other",
+];
+const DEFAULT_MIN_TEXT_LENGTH: usize = 20;
+const DEFAULT_CRAWL_DEPTH: usize = 1;
+const MAX_ACTION_WAIT_MS: u64 = 60_000;
+const DEFAULT_LAZY_SCROLLS: usize = 5;
+const MAX_LAZY_SCROLLS: usize = 200;
+const DEFAULT_CRAWL_PAGES: usize = 10;
+const MAX_CRAWL_PAGES: usize = 200;
+const MAX_ADAPTIVE_SCROLLS: usize = 30;
+const DATE_MONTHS: &str =
+    "Jan(?:uary)?|Feb(?:ruary)?|Mar(?:ch)?|Apr(?:il)?|May|June?|July?|Aug(?:ust)?|Sep(?:t(?:ember)?)?|Oct(?:ober)?|Nov(?:ember)?|Dec(?:ember)?";
+const MAX_SCRAPE_RETRIES: u32 = 5;
+const RETRY_BASE_DELAY_MS: u64 = 500;
+
+type CapturedRequests = Arc<Mutex<Vec<(RequestId, String, i64)>>>;
+type BlockedRequests = Arc<Mutex<Vec<BlockedRequest>>>;
+type TitleHistory = Arc<Mutex<Vec<String>>>;
+type OpenedTabs = Arc<Mutex<Vec<TargetId>>>;
+
+static ACTIVE_BROWSERS: AtomicUsize = AtomicUsize::new(0);
+
+fn max_total_browsers() -> usize {
+    static MAX: OnceLock<usize> = OnceLock::new();
+    *MAX.get_or_init(|| {
+        std::env::var("MAX_TOTAL_BROWSERS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(8)
+    })
+}
+
+fn custom_js_enabled() -> bool {
+    std::env::var("ENABLE_CUSTOM_JS")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+fn launch_timeout() -> Duration {
+    static TIMEOUT: OnceLock<Duration> = OnceLock::new();
+    *TIMEOUT.get_or_init(|| {
+        let secs = std::env::var("BROWSER_LAUNCH_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+        Duration::from_secs(secs)
+    })
+}
+
+const DEFAULT_USER_AGENT: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/129.0.0.0 Safari/537.36";
+
+fn user_agent_pool() -> &'static [String] {
+    static POOL: OnceLock<Vec<String>> = OnceLock::new();
+    POOL.get_or_init(|| {
+        std::env::var("USER_AGENTS")
+            .map(|v| {
+                v.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn pick_user_agent() -> String {
+    let pool = user_agent_pool();
+    if pool.is_empty() {
+        return DEFAULT_USER_AGENT.to_string();
+    }
+    pool[rand::rng().random_range(0..pool.len())].clone()
+}
+
+pub(crate) fn platform_for_user_agent(user_agent: &str) -> &'static str {
+    if user_agent.contains("iPhone") {
+        "iPhone"
+    } else if user_agent.contains("iPad") {
+        "iPad"
+    } else if user_agent.contains("Android") {
+        "Linux armv8l"
+    } else if user_agent.contains("Macintosh") {
+        "MacIntel"
+    } else if user_agent.contains("Linux") || user_agent.contains("X11") {
+        "Linux x86_64"
+    } else {
+        "Win32"
+    }
+}
+
+struct DeviceProfile {
+    width: i64,
+    height: i64,
+    scale: f64,
+    mobile: bool,
+    user_agent: &'static str,
+}
+
+const DESKTOP: DeviceProfile = DeviceProfile {
+    width: 1920,
+    height: 1080,
+    scale: 1.0,
+    mobile: false,
+    user_agent: DEFAULT_USER_AGENT,
+};
+
+const DEVICES: &[(&str, DeviceProfile)] = &[
+    ("iphone_13", DeviceProfile {
+        width: 390,
+        height: 844,
+        scale: 3.0,
+        mobile: true,
+        user_agent: "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1",
+    }),
+    ("iphone_se", DeviceProfile {
+        width: 375,
+        height: 667,
+        scale: 2.0,
+        mobile: true,
+        user_agent: "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1",
+    }),
+    ("pixel_7", DeviceProfile {
+        width: 412,
+        height: 915,
+        scale: 2.625,
+        mobile: true,
+        user_agent: "Mozilla/5.0 (Linux; Android 14; Pixel 7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/129.0.0.0 Mobile Safari/537.36",
+    }),
+    ("galaxy_s22", DeviceProfile {
+        width: 360,
+        height: 780,
+        scale: 3.0,
+        mobile: true,
+        user_agent: "Mozilla/5.0 (Linux; Android 14; SM-S901B) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/129.0.0.0 Mobile Safari/537.36",
+    }),
+    ("ipad_air", DeviceProfile {
+        width: 820,
+        height: 1180,
+        scale: 2.0,
+        mobile: true,
+        user_agent: "Mozilla/5.0 (iPad; CPU OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1",
+    }),
+];
+
+fn device_profile(name: Option<&str>) -> Result<Option<&'static DeviceProfile>, ScrapeError> {
+    let Some(name) = name else {
+        return Ok(None);
+    };
+    DEVICES
+        .iter()
+        .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+        .map(|(_, profile)| Some(profile))
+        .ok_or_else(|| ScrapeError::InvalidRequest(format!("Unknown device: {}", name)))
+}
+
+struct Interception {
+    page: Page,
+    handles: Vec<task::JoinHandle<()>>,
+}
+
+impl Drop for Interception {
+    fn drop(&mut self) {
+        let page = self.page.clone();
+        tokio::spawn(async move {
+            let _ = page.execute(FetchDisableParams::default()).await;
+        });
+        self.handles.iter().for_each(|h| h.abort());
+    }
+}
+
+pub(crate) struct BrowserSlot;
+
+impl BrowserSlot {
+    pub(crate) fn acquire() -> Result<Self, ScrapeError> {
+        let max = max_total_browsers();
+        ACTIVE_BROWSERS
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| (n < max).then_some(n + 1))
+            .map(|_| BrowserSlot)
+            .map_err(|_| ScrapeError::BrowserLimitReached(max))
+    }
+}
+
+impl Drop for BrowserSlot {
+    fn drop(&mut self) {
+        ACTIVE_BROWSERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+const EVASIONS: &[(&str, &str)] = &[
+    ("webdriver", r#"
+            Object.defineProperty(navigator, 'webdriver', { get: () => undefined });"#),
+    ("plugins", r#"
+            Object.defineProperty(navigator, 'plugins', { get: () => [1, 2, 3] });"#),
+    ("languages", r#"
+            Object.defineProperty(navigator, 'languages', { get: () => ['en-US', 'en'] });"#),
+    ("permissions", r#"
+            const originalQuery = window.navigator.permissions.query;
+            window.navigator.permissions.query = (parameters) => (
+                parameters.name === 'notifications' ?
+                Promise.resolve({ state: Notification.permission }) :
+                originalQuery(parameters)
+            );"#),
+    ("webgl", r#"
+            try {
+                const getParameter = WebGLRenderingContext.prototype.getParameter;
+                WebGLRenderingContext.prototype.getParameter = function(parameter) {
+                    if (parameter === 37445) return 'Intel Open Source Technology Center';
+                    if (parameter === 37446) return 'Mesa DRI Intel(R) HD Graphics 4000 (IVB GT2)';
+                    return getParameter.call(this, parameter);
+                };
+            } catch (e) {}"#),
+];
+
+fn build_evasion_script(selected: Option<&[String]>) -> String {
+    EVASIONS
+        .iter()
+        .filter(|(name, _)| {
+            selected.is_none_or(|list| list.iter().any(|s| s.eq_ignore_ascii_case(name)))
+        })
+        .map(|(_, script)| *script)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    pub headless: bool,
+    pub resource_limits: Option<ResourceLimits>,
+    pub proxy: Option<ProxyConfig>,
+    pub request_timeout: Option<Duration>,
+}
+
+fn next_pool_proxy(pool: &[String]) -> Option<String> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    if pool.is_empty() {
+        return None;
+    }
+    Some(pool[NEXT.fetch_add(1, Ordering::Relaxed) % pool.len()].clone())
+}
+
+fn validate_proxy(proxy: &ProxyConfig) -> Result<(), String> {
+    if !["http", "https", "socks4", "socks5"].contains(&proxy.scheme()) {
+        return Err(format!("unsupported proxy scheme {}", proxy.scheme()));
+    }
+    if proxy.host.is_empty() {
+        return Err("proxy host is empty".to_string());
+    }
+    if proxy.scheme().starts_with("socks") && proxy.username.is_some() {
+        return Err("Chromium does not support authenticated SOCKS proxies".to_string());
+    }
+    Ok(())
+}
+
+fn env_proxy() -> Option<&'static ProxyConfig> {
+    static PROXY: OnceLock<Option<ProxyConfig>> = OnceLock::new();
+    PROXY
+        .get_or_init(|| {
+            let url = std::env::var("PROXY_URL").ok().filter(|v| !v.is_empty())?;
+            match ProxyConfig::parse(&url).filter(|proxy| validate_proxy(proxy).is_ok()) {
+                Some(proxy) => Some(proxy),
+                None => {
+                    tracing::warn!("Ignoring invalid PROXY_URL");
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+impl LaunchOptions {
+    pub fn from_request(req: &ScrapeRequest) -> Result<Self, ScrapeError> {
+        if let Some(limits) = &req.resource_limits {
+            if let Some(heap) = limits.max_heap_mb
+                && !(64..=16384).contains(&heap)
+            {
+                return Err(ScrapeError::InvalidRequest(format!(
+                    "max_heap_mb must be between 64 and 16384, got {}",
+                    heap
+                )));
+            }
+            if let Some(renderers) = limits.max_renderer_processes
+                && !(1..=32).contains(&renderers)
+            {
+                return Err(ScrapeError::InvalidRequest(format!(
+                    "max_renderer_processes must be between 1 and 32, got {}",
+                    renderers
+                )));
+            }
+        }
+
+        let proxy = match (&req.proxy, req.proxy_pool.as_deref().and_then(next_pool_proxy)) {
+            (Some(setting), _) => Some(
+                setting
+                    .resolve()
+                    .ok_or_else(|| ScrapeError::InvalidRequest("Invalid proxy".to_string()))?,
+            ),
+            (None, Some(url)) => Some(
+                ProxyConfig::parse(&url)
+                    .ok_or_else(|| ScrapeError::InvalidRequest(format!("Invalid proxy: {}", url)))?,
+            ),
+            (None, None) => None,
+        };
+        if let Some(proxy) = &proxy {
+            validate_proxy(proxy)
+                .map_err(|e| ScrapeError::InvalidRequest(format!("Invalid proxy {}: {}", proxy.server(), e)))?;
+        }
+
+        let request_timeout = req
+            .timeouts
+            .as_ref()
+            .map(TimeoutConfig::navigation)
+            .filter(|timeout| timeout.as_millis() > TimeoutConfig::DEFAULT_NAVIGATION_MS as u128);
+
+        Ok(Self {
+            headless: true,
+            resource_limits: req.resource_limits.clone(),
+            proxy,
+            request_timeout,
+        })
+    }
+
+    fn uses_defaults(&self) -> bool {
+        self.headless
+            && self.resource_limits.is_none()
+            && self.proxy.is_none()
+            && self.request_timeout.is_none()
+    }
+}
+
+fn spawn_handler(mut handler: Handler) -> task::JoinHandle<()> {
+    task::spawn(async move {
+        while handler.next().await.is_some() {
+
+        }
+    })
+}
+
+pub(crate) async fn launch_browser(
+    options: &LaunchOptions,
+) -> Result<(Browser, task::JoinHandle<()>), ScrapeError> {
+    let mut builder = BrowserConfig::builder()
+        .request_timeout(
+            options
+                .request_timeout
+                .unwrap_or(Duration::from_millis(TimeoutConfig::DEFAULT_NAVIGATION_MS)),
+        )
+        .no_sandbox()
+        .arg("--disable-dev-shm-usage")
+        .arg("--disable-blink-features=AutomationControlled")
+        .arg("--disable-extensions")
+        .arg("--disable-gpu")
+        .arg("--disable-software-rasterizer");
+
+    if options.headless {
+        builder = builder.headless_mode(HeadlessMode::True);
+    } else {
+        builder = builder.headless_mode(HeadlessMode::False);
+    }
+
+    if let Some(limits) = &options.resource_limits {
+        if let Some(heap) = limits.max_heap_mb {
+            builder = builder
+                .arg(format!("--js-flags=--max-old-space-size={}", heap))
+                .arg("--memory-pressure-off");
+        }
+        if let Some(renderers) = limits.max_renderer_processes {
+            builder = builder.arg(format!("--renderer-process-limit={}", renderers));
+        }
+    }
+
+    if let Some(proxy) = options.proxy.as_ref().or(env_proxy()) {
+        builder = builder.arg(format!("--proxy-server={}", proxy.server()));
+    }
+
+    let (browser, handler) = tokio::time::timeout(
+        launch_timeout(),
+        Browser::launch(builder.build().unwrap()),
+    )
+    .await
+    .map_err(|_| ScrapeError::BrowserLaunch("launch timed out".to_string()))?
+    .map_err(|e| ScrapeError::BrowserLaunch(e.to_string()))?;
+
+    shutdown::register(&browser);
+    Ok((browser, spawn_handler(handler)))
+}
+
 pub struct Scraper {
     browser: Option<Browser>,
     page: Page,
-    _handler_handle: task::JoinHandle<()>,
+    active_page: Mutex<Option<Page>>,
+    owns_browser: bool,
+    lease: Option<PoolLease>,
+    proxy: Option<ProxyConfig>,
+    proxy_auth: Option<(String, String)>,
+    user_agent: String,
+    evasion_scripts: Mutex<HashMap<TargetId, ScriptIdentifier>>,
+    _slot: Option<BrowserSlot>,
+    handler_handle: Option<task::JoinHandle<()>>,
+}
+
+struct ScrapeSetup {
+    _interception: Option<Interception>,
+    images_blocked: bool,
+    warmup_performed: bool,
+    login_attempted: bool,
+    login_success: Option<bool>,
+    platform_detected: Option<String>,
+    requires_2fa: Option<bool>,
+    login_frame: Option<String>,
+    warnings: Vec<String>,
+}
+
+struct TargetNavigation<'a> {
+    scraper: &'a Scraper,
+    referer: Option<&'a str>,
+    max_redirects: usize,
+    timeout: Duration,
+    chain: Mutex<Vec<String>>,
+}
+
+impl<'a> TargetNavigation<'a> {
+    fn new(scraper: &'a Scraper, req: &'a ScrapeRequest, timeouts: &TimeoutConfig) -> Self {
+        Self {
+            scraper,
+            referer: req.referer.as_deref(),
+            max_redirects: req.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS),
+            timeout: timeouts.navigation(),
+            chain: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn into_chain(self) -> Vec<String> {
+        self.chain.into_inner().unwrap_or_default()
+    }
+}
+
+impl Navigator for TargetNavigation<'_> {
+    async fn location(&self) -> String {
+        self.scraper.current_url().await
+    }
+
+    async fn navigate_to(&self, url: &str) -> Result<(), ScrapeError> {
+        emit("navigation", format!("Navigating to {}", url));
+        let chain = tokio::time::timeout(
+            self.timeout,
+            self.scraper.navigate(url, self.referer, self.max_redirects),
+        )
+        .await
+        .map_err(|_| {
+            ScrapeError::Navigation(format!(
+                "navigation to {} timed out after {}ms",
+                url,
+                self.timeout.as_millis()
+            ))
+        })??;
+        if let Ok(mut list) = self.chain.lock() {
+            *list = chain;
+        }
+        Ok(())
+    }
 }
 
 impl Scraper {
     pub async fn new(headless: bool) -> Result<Self, ScrapeError> {
-        let mut builder = BrowserConfig::builder()
-            .request_timeout(Duration::from_secs(30))
-            .no_sandbox()
-            .arg("--disable-dev-shm-usage")
-            .arg("--disable-blink-features=AutomationControlled")
-            .arg("--disable-extensions")
-            .arg("--disable-gpu")
-            .arg("--disable-software-rasterizer");
-
-        if headless {
-            builder = builder.headless_mode(HeadlessMode::True);
-        } else {
-            builder = builder.headless_mode(HeadlessMode::False);
-        }
+        Self::with_options(&LaunchOptions { headless, ..Default::default() }).await
+    }
 
-        let (mut browser, mut handler) = Browser::launch(builder.build().unwrap())
-            .await
-            .map_err(|e| ScrapeError::BrowserLaunch(e.to_string()))?;
+    pub async fn with_options(options: &LaunchOptions) -> Result<Self, ScrapeError> {
+        let slot = BrowserSlot::acquire()?;
 
-        let _handler_handle = task::spawn(async move {
-            while handler.next().await.is_some() {
+        let (browser, handler_handle) = launch_browser(options).await?;
 
-            }
-        });
+        let mut scraper = Self::from_browser(browser, handler_handle, Some(slot), None).await?;
+        scraper.proxy = options.proxy.clone().or_else(|| env_proxy().cloned());
+        scraper.proxy_auth = scraper.proxy.as_ref().and_then(ProxyConfig::credentials);
+        Ok(scraper)
+    }
 
+    pub async fn from_pool(pooled: PooledBrowser) -> Result<Self, ScrapeError> {
+        let mut scraper = Self::from_browser(pooled.browser, pooled.handler, None, Some(pooled.lease)).await?;
+        scraper.proxy = env_proxy().cloned();
+        scraper.proxy_auth = scraper.proxy.as_ref().and_then(ProxyConfig::credentials);
+        Ok(scraper)
+    }
 
-        tokio::time::sleep(Duration::from_millis(500)).await;
+    pub async fn connect(ws_url: &str) -> Result<Self, ScrapeError> {
+        let (browser, handler) = Browser::connect(ws_url)
+            .await
+            .map_err(|e| ScrapeError::BrowserLaunch(format!("Connect to {}: {}", ws_url, e)))?;
 
-        let page = browser
-            .new_page("about:blank")
+        Self::from_browser(browser, spawn_handler(handler), None, None).await
+    }
+
+    async fn from_browser(
+        browser: Browser,
+        handler_handle: task::JoinHandle<()>,
+        slot: Option<BrowserSlot>,
+        lease: Option<PoolLease>,
+    ) -> Result<Self, ScrapeError> {
+        let owns_browser = slot.is_some();
+        let warm = lease.is_some();
+
+        if !warm {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+
+        let page = tokio::time::timeout(launch_timeout(), browser.new_page("about:blank"))
             .await
+            .map_err(|_| ScrapeError::PageCreation("new page timed out".to_string()))?
             .map_err(|e| ScrapeError::PageCreation(e.to_string()))?;
 
-        tokio::time::sleep(Duration::from_millis(500)).await;
-
-        Self::setup_evasions(&page).await?;
+        if !warm {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
 
         Ok(Self {
             browser: Some(browser),
             page,
-            _handler_handle,
+            active_page: Mutex::new(None),
+            evasion_scripts: Mutex::new(HashMap::new()),
+            owns_browser,
+            lease,
+            proxy: None,
+            proxy_auth: None,
+            user_agent: pick_user_agent(),
+            _slot: slot,
+            handler_handle: Some(handler_handle),
         })
     }
 
-    async fn setup_evasions(page: &Page) -> Result<(), ScrapeError> {
-        page.execute(SetUserAgentOverrideParams::new(
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/129.0.0.0 Safari/537.36"
-        ))
+    async fn setup_evasions(
+        &self,
+        page: &Page,
+        evasions: Option<&[String]>,
+        user_agent: &str,
+        device: Option<&DeviceProfile>,
+    ) -> Result<(), ScrapeError> {
+        page.execute(SetUserAgentOverrideParams {
+            user_agent: user_agent.to_string(),
+            accept_language: Some("en-US,en".to_string()),
+            platform: Some(platform_for_user_agent(user_agent).to_string()),
+            user_agent_metadata: None,
+        })
         .await
         .map_err(|e| ScrapeError::EvaluationFailed(format!("Set User Agent: {}", e)))?;
 
+        let device = device.unwrap_or(&DESKTOP);
         page.execute(
             SetDeviceMetricsOverrideParams::builder()
-                .width(1920)
-                .height(1080)
-                .device_scale_factor(1.0)
-                .mobile(false)
+                .width(device.width)
+                .height(device.height)
+                .device_scale_factor(device.scale)
+                .mobile(device.mobile)
                 .build()
                 .unwrap(),
         )
         .await
         .map_err(|e| ScrapeError::EvaluationFailed(format!("Set Viewport: {}", e)))?;
 
-        let evasion_script = r#"
-            Object.defineProperty(navigator, 'webdriver', { get: () => undefined });
-            Object.defineProperty(navigator, 'plugins', { get: () => [1, 2, 3] });
-            Object.defineProperty(navigator, 'languages', { get: () => ['en-US', 'en'] });
-            const originalQuery = window.navigator.permissions.query;
-            window.navigator.permissions.query = (parameters) => (
-                parameters.name === 'notifications' ?
-                Promise.resolve({ state: Notification.permission }) :
-                originalQuery(parameters)
-            );
-            try {
-                const getParameter = WebGLRenderingContext.prototype.getParameter;
-                WebGLRenderingContext.prototype.getParameter = function(parameter) {
-                    if (parameter === 37445) return 'Intel Open Source Technology Center';
-                    if (parameter === 37446) return 'Mesa DRI Intel(R) HD Graphics 4000 (IVB GT2)';
-                    return getParameter.call(this, parameter);
-                };
-            } catch (e) {}
-        "#.to_string();
-
-        page.execute(AddScriptToEvaluateOnNewDocumentParams {
-            source: evasion_script,
-            world_name: None,
-            include_command_line_api: None,
-            run_immediately: None,
-        })
-        .await
-        .map_err(|e| ScrapeError::EvaluationFailed(format!("Add Evasion Script: {}", e)))?;
+        let mut touch = SetTouchEmulationEnabledParams::new(device.mobile);
+        if device.mobile {
+            touch.max_touch_points = Some(5);
+        }
+        page.execute(touch)
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Set Touch Emulation: {}", e)))?;
+
+        let previous = self
+            .evasion_scripts
+            .lock()
+            .ok()
+            .and_then(|mut scripts| scripts.remove(page.target_id()));
+        if let Some(identifier) = previous {
+            page.execute(RemoveScriptToEvaluateOnNewDocumentParams::new(identifier))
+                .await
+                .map_err(|e| ScrapeError::EvaluationFailed(format!("Remove Evasion Script: {}", e)))?;
+        }
+
+        let evasion_script = build_evasion_script(evasions);
+        if evasion_script.is_empty() {
+            return Ok(());
+        }
+
+        let identifier = page
+            .execute(AddScriptToEvaluateOnNewDocumentParams {
+                source: evasion_script,
+                world_name: None,
+                include_command_line_api: None,
+                run_immediately: None,
+            })
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Add Evasion Script: {}", e)))?
+            .result
+            .identifier;
+        if let Ok(mut scripts) = self.evasion_scripts.lock() {
+            scripts.insert(page.target_id().clone(), identifier);
+        }
 
         Ok(())
     }
-    async fn scroll_for_lazy_content(&self) -> Result<(), ScrapeError> {
-        let mut last_height: i64 = -1;
-        for _ in 0..5 {
-            let new_height = self
-                .page
-                .evaluate("window.scrollTo(0, document.body.scrollHeight); document.body.scrollHeight;")
-                .await
-                .map_err(|e| ScrapeError::EvaluationFailed(e.to_string()))?
-                .into_value::<i64>()
-                .unwrap_or(0);
 
-            if new_height == last_height {
-                break;
+    fn user_agent_for<'a>(&'a self, req: &'a ScrapeRequest, device: Option<&DeviceProfile>) -> &'a str {
+        req.user_agent
+            .as_deref()
+            .or(device.map(|d| d.user_agent))
+            .unwrap_or(&self.user_agent)
+    }
+
+    fn page(&self) -> Page {
+        self.active_page
+            .lock()
+            .ok()
+            .and_then(|active| active.clone())
+            .unwrap_or_else(|| self.page.clone())
+    }
+
+    async fn start_tab_watch(&self) -> Result<(task::JoinHandle<()>, OpenedTabs), ScrapeError> {
+        let browser = self
+            .browser
+            .as_ref()
+            .ok_or_else(|| ScrapeError::PageCreation("Browser already closed".to_string()))?;
+        let mut created = browser
+            .event_listener::<EventTargetCreated>()
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Listen Targets: {}", e)))?;
+
+        let opener = self.page.target_id().clone();
+        let opened: OpenedTabs = Arc::new(Mutex::new(Vec::new()));
+        let sink = opened.clone();
+        let handle = task::spawn(async move {
+            while let Some(event) = created.next().await {
+                let info = &event.target_info;
+                if info.r#type == "page"
+                    && info.opener_id.as_ref() == Some(&opener)
+                    && let Ok(mut list) = sink.lock()
+                {
+                    list.push(info.target_id.clone());
+                }
             }
-            last_height = new_height;
-            tokio::time::sleep(Duration::from_millis(1500)).await;
+        });
+
+        Ok((handle, opened))
+    }
+
+    async fn switch_to_new_tab(
+        &self,
+        opened: &OpenedTabs,
+        body_wait: Duration,
+    ) -> Result<Option<String>, ScrapeError> {
+        let Some(target_id) = opened.lock().ok().and_then(|list| list.last().cloned()) else {
+            return Ok(None);
+        };
+        let browser = self
+            .browser
+            .as_ref()
+            .ok_or_else(|| ScrapeError::PageCreation("Browser already closed".to_string()))?;
+
+        let mut attempts = 0;
+        let page = loop {
+            match browser.get_page(target_id.clone()).await {
+                Ok(page) => break page,
+                Err(_) if attempts < 10 => {
+                    attempts += 1;
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                }
+                Err(e) => return Err(ScrapeError::PageCreation(format!("New tab: {}", e))),
+            }
+        };
+        if let Ok(mut active) = self.active_page.lock() {
+            *active = Some(page);
         }
-        let _ = self.page.evaluate("window.scrollTo(0, 0);").await;
-        tokio::time::sleep(Duration::from_millis(500)).await;
-        Ok(())
+        self.wait_for_body(body_wait).await?;
+        Ok(Some(self.current_url().await))
+    }
+
+    async fn evaluate_value<T: DeserializeOwned>(&self, script: impl Into<Evaluation>) -> Result<T, ScrapeError> {
+        self.page()
+            .evaluate(script)
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(e.to_string()))?
+            .into_value::<T>()
+            .map_err(|e| ScrapeError::EvaluationFailed(e.to_string()))
     }
 
-    pub async fn scrape(
+    async fn navigate(
         &self,
         url: &str,
-        login: Option<LoginCredentials>,
-    ) -> Result<ScrapedData, ScrapeError> {
-        let (login_attempted, login_success, platform_detected, requires_2fa) =
-            if let Some(credentials) = login {
-                match auto_login(&self.page, &credentials, url).await {
-                    Ok((success, platform, tfa)) => {
-                        if tfa.unwrap_or(false) {
-                            return Err(ScrapeError::TwoFactorAuthRequired);
-                        }
-                        if !success {
-                        }
-                        (true, Some(success), platform, tfa)
-                    }
-                    Err(e) => {
-                        (true, Some(false), None, None)
-                    }
-                }
-            } else {
-                (false, None, None, None)
-            };
+        referer: Option<&str>,
+        max_redirects: usize,
+    ) -> Result<Vec<String>, ScrapeError> {
+        let mut params = NavigateParams::builder().url(url);
+        if let Some(referer) = referer {
+            params = params.referrer(referer);
+        }
+        let params = params.build().map_err(ScrapeError::Navigation)?;
 
-        let current_url = self
-            .page
-            .evaluate("window.location.href")
+        let main_frame = self.page().mainframe().await.ok().flatten();
+        let mut events = self
+            .page()
+            .event_listener::<EventRequestWillBeSent>()
             .await
-            .ok()
-            .and_then(|v| v.into_value::<String>().ok())
-            .unwrap_or_default();
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Listen Requests: {}", e)))?;
 
-        if !current_url.starts_with(url) {
-            
-            let nav_result = self.page.goto(url).await;
-            
-            match nav_result {
-                Ok(_) => {
-                    tokio::time::sleep(Duration::from_millis(2000)).await;
+        let chain = Arc::new(Mutex::new(Vec::new()));
+        let redirects = Arc::new(AtomicUsize::new(0));
+        let (sink, counter) = (chain.clone(), redirects.clone());
+        let tracker = task::spawn(async move {
+            while let Some(event) = events.next().await {
+                let is_document = event.r#type == Some(ResourceType::Document);
+                let in_main_frame = main_frame.is_none() || event.frame_id == main_frame;
+                if !is_document || !in_main_frame {
+                    continue;
                 }
-                Err(e) => {
-                    return Err(ScrapeError::Navigation(format!("Failed to navigate: {}", e)));
+                if event.redirect_response.is_some() {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }
+                if let Ok(mut list) = sink.lock() {
+                    list.push(event.request.url.clone());
+                }
+            }
+        });
+
+        let too_many = async {
+            while redirects.load(Ordering::SeqCst) <= max_redirects {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        };
+
+        let page = self.page();
+        let result = tokio::select! {
+            res = page.goto(params) => res.map(|_| ()).map_err(|e| e.to_string()),
+            _ = too_many => Err("ERR_TOO_MANY_REDIRECTS".to_string()),
+        };
+        tracker.abort();
+
+        if let Err(message) = result {
+            if message.contains("ERR_TOO_MANY_REDIRECTS") {
+                return Err(ScrapeError::Navigation(format!(
+                    "too many redirects (limit {})",
+                    max_redirects
+                )));
+            }
+            if message.contains("ERR_CERT") || message.contains("ERR_SSL") {
+                return Err(ScrapeError::InvalidCertificate(message));
+            }
+            return Err(ScrapeError::Navigation(format!("Failed to navigate: {}", message)));
+        }
+
+        Ok(chain.lock().map(|list| list.clone()).unwrap_or_default())
+    }
+
+    async fn current_url(&self) -> String {
+        self.evaluate_value::<String>("window.location.href")
+            .await
+            .unwrap_or_default()
+    }
+
+    async fn follow_client_redirects(
+        &self,
+        chain: &mut Vec<String>,
+        max_redirects: usize,
+        body_wait: Duration,
+    ) -> Result<(), ScrapeError> {
+        for _ in 0..max_redirects {
+            let before = self.current_url().await;
+            let meta_refresh = self
+                .evaluate_value::<Option<(f64, String)>>(
+                    r#"(() => {
+                    const meta = document.querySelector('meta[http-equiv="refresh" i]');
+                    if (!meta) return null;
+                    const content = meta.getAttribute('content') || '';
+                    const match = content.match(/^\s*(\d+(?:\.\d+)?)\s*(?:[;,]\s*(?:url\s*=\s*)?['"]?([^'"]*)['"]?)?/i);
+                    if (!match || !match[2]) return null;
+                    return [parseFloat(match[1]), new URL(match[2].trim(), window.location.href).href];
+                })()"#,
+                )
+                .await
+                .ok()
+                .flatten();
+
+            let wait = match &meta_refresh {
+                Some((delay, _)) if *delay <= 10.0 => Duration::from_secs_f64(*delay) + Duration::from_secs(3),
+                Some(_) => return Ok(()),
+                None => Duration::from_secs(3),
+            };
+
+            let started = std::time::Instant::now();
+            let mut after = before.clone();
+            while started.elapsed() < wait && after == before {
+                tokio::time::sleep(Duration::from_millis(250)).await;
+                after = self.current_url().await;
+            }
+
+            if after == before {
+                match meta_refresh {
+                    Some((_, target)) if target != before => {
+                        self.page()
+                            .goto(target.as_str())
+                            .await
+                            .map_err(|e| ScrapeError::Navigation(format!("Meta refresh: {}", e)))?;
+                        after = target;
+                    }
+                    _ => return Ok(()),
                 }
             }
+
+            chain.push(after);
+            tokio::time::sleep(Duration::from_millis(1000)).await;
+            self.wait_for_body(body_wait).await?;
         }
-        let wait_result = tokio::time::timeout(
-            Duration::from_secs(10),
+
+        Err(ScrapeError::Navigation(format!(
+            "too many redirects (limit {})",
+            max_redirects
+        )))
+    }
+
+    async fn stealth_navigate(&self, url: &str) -> Result<(), ScrapeError> {
+        self.page()
+            .goto(url)
+            .await
+            .map_err(|e| ScrapeError::Navigation(format!("Warm-up navigation: {}", e)))?;
+
+        let (settle_ms, scroll_px) = {
+            let mut rng = rand::rng();
+            (rng.random_range(2000..4500), rng.random_range(200..800))
+        };
+        tokio::time::sleep(Duration::from_millis(settle_ms / 2)).await;
+        let _ = self
+            .page()
+            .evaluate(format!("window.scrollBy({{ top: {}, behavior: 'smooth' }});", scroll_px))
+            .await;
+        tokio::time::sleep(Duration::from_millis(settle_ms / 2)).await;
+        Ok(())
+    }
+
+    async fn wait_for_body(&self, timeout: Duration) -> Result<(), ScrapeError> {
+        tokio::time::timeout(
+            timeout,
             async {
-                for _ in 0..20 {
-                    if self.page.find_element("body").await.is_ok() {
+                loop {
+                    if self.page().find_element("body").await.is_ok() {
                         return Ok(());
                     }
                     tokio::time::sleep(Duration::from_millis(500)).await;
                 }
-                Err(ScrapeError::ContentExtraction("Body element not found".to_string()))
             }
         )
         .await
-        .map_err(|_| ScrapeError::ContentExtraction("Timeout waiting for body element".to_string()))?;
-        
-        wait_result?;
-        
-        self.scroll_for_lazy_content().await?;
+        .map_err(|_| ScrapeError::Navigation("Timeout waiting for body element".to_string()))?
+    }
 
-        let title = self.page.get_title().await.ok().flatten();
+    async fn wait_for_dom_stable(&self, stable: &DomStableWait) -> Result<(), ScrapeError> {
+        let quiet_ms = stable.quiet_ms.unwrap_or(500);
+        let timeout_ms = stable.timeout_ms.unwrap_or(10000);
 
-        let description = self
-            .page
-            .evaluate(r#"
-                (() => {
+        self.evaluate_value::<bool>(
+            r#"(() => {
+                window.__scraperLastMutation = Date.now();
+                if (window.__scraperObserver) return true;
+                window.__scraperObserver = new MutationObserver(() => {
+                    window.__scraperLastMutation = Date.now();
+                });
+                window.__scraperObserver.observe(document.documentElement, {
+                    childList: true, subtree: true, attributes: true, characterData: true
+                });
+                return true;
+            })()"#,
+        )
+        .await?;
+
+        let started = std::time::Instant::now();
+        while started.elapsed() < Duration::from_millis(timeout_ms) {
+            let idle_ms = self
+                .evaluate_value::<u64>("Date.now() - window.__scraperLastMutation")
+                .await
+                .unwrap_or(0);
+            if idle_ms >= quiet_ms {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        let _ = self
+            .page()
+            .evaluate("window.__scraperObserver && window.__scraperObserver.disconnect(); delete window.__scraperObserver;")
+            .await;
+        Ok(())
+    }
+
+    async fn wait_for_text(&self, wait: &TextWait) -> Result<(), ScrapeError> {
+        let timeout_ms = wait.timeout_ms.unwrap_or(10000);
+        let script = format!(
+            r#"(() => {{
+                const el = document.querySelector({});
+                if (!el) return false;
+                const text = (el.innerText || el.textContent || '').trim();
+                const expected = {};
+                return expected === null ? text.length > 0 : text.includes(expected);
+            }})()"#,
+            serde_json::to_string(&wait.selector).unwrap_or_default(),
+            serde_json::to_string(&wait.contains).unwrap_or_default()
+        );
+
+        let started = std::time::Instant::now();
+        while started.elapsed() < Duration::from_millis(timeout_ms) {
+            if self.evaluate_value::<bool>(script.as_str()).await.unwrap_or(false) {
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+
+        Err(ScrapeError::ContentExtraction(match &wait.contains {
+            Some(expected) => format!(
+                "timed out after {}ms waiting for '{}' to contain '{}'",
+                timeout_ms, wait.selector, expected
+            ),
+            None => format!(
+                "timed out after {}ms waiting for '{}' to have text",
+                timeout_ms, wait.selector
+            ),
+        }))
+    }
+
+    async fn detect_framework(&self) -> Option<String> {
+        self.evaluate_value::<Option<String>>(
+            r#"(() => {
+                if (window.__NEXT_DATA__ || document.getElementById('__NEXT_DATA__')) return 'nextjs';
+                if (window.__NUXT__) return 'nuxt';
+                if (window.ng || window.getAllAngularTestabilities || document.querySelector('[ng-version]')) return 'angular';
+                if (window.__VUE__ || window.Vue || document.querySelector('[data-v-app], [data-server-rendered]')) return 'vue';
+                if (window.React || window.__REACT_DEVTOOLS_GLOBAL_HOOK__ || document.querySelector('[data-reactroot]')) return 'react';
+                if (window.__svelte || document.querySelector('[class*="svelte-"]')) return 'svelte';
+                return null;
+            })()"#,
+        )
+        .await
+        .ok()
+        .flatten()
+    }
+
+    async fn wait_for_framework(&self, framework: &str) {
+        let ready_check = match framework {
+            "nextjs" => "document.readyState === 'complete' && !!(window.next && window.next.router ? window.next.router.isReady !== false : true)",
+            "nuxt" => "document.readyState === 'complete' && !!(window.$nuxt ? window.$nuxt.$el : true)",
+            "angular" => r#"(() => {
+                if (!window.getAllAngularTestabilities) return document.readyState === 'complete';
+                return window.getAllAngularTestabilities().every(t => t.isStable());
+            })()"#,
+            "vue" | "react" | "svelte" => r#"(() => {
+                const root = document.querySelector('#app, #root, #__next, [data-reactroot], [data-v-app], main');
+                return document.readyState === 'complete' && (!root || root.children.length > 0);
+            })()"#,
+            _ => return,
+        };
+        for _ in 0..20 {
+            if self.evaluate_value::<bool>(ready_check).await.unwrap_or(true) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    async fn trigger_spa_route(&self, fragment: &str) -> Result<(), ScrapeError> {
+        let script = format!(
+            r#"(() => {{
+                const hash = '#' + {};
+                if (window.location.hash !== hash) {{
+                    window.location.hash = hash;
+                }}
+                window.dispatchEvent(new HashChangeEvent('hashchange', {{ newURL: window.location.href }}));
+                window.dispatchEvent(new PopStateEvent('popstate', {{ state: history.state }}));
+                return true;
+            }})()"#,
+            serde_json::to_string(fragment).unwrap_or_default()
+        );
+        self.page()
+            .evaluate(script)
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("SPA route: {}", e)))?;
+        tokio::time::sleep(Duration::from_millis(2000)).await;
+        Ok(())
+    }
+
+    async fn run_search(&self, search: &SearchAction) -> Result<(), ScrapeError> {
+        if !type_into_field(&self.page(), &search.selector, &search.query)
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Search input: {}", e)))?
+        {
+            return Err(ScrapeError::ContentExtraction(format!(
+                "Search field not found: {}",
+                search.selector
+            )));
+        }
+
+        let submitted = match &search.submit_selector {
+            Some(selector) => click_element(&self.page(), selector).await,
+            None => press_enter(&self.page(), &search.selector).await,
+        }
+        .map_err(|e| ScrapeError::EvaluationFailed(format!("Search submit: {}", e)))?;
+
+        if !submitted {
+            return Err(ScrapeError::ContentExtraction("Could not submit search".to_string()));
+        }
+
+        tokio::time::sleep(Duration::from_millis(3000)).await;
+        Ok(())
+    }
+
+    async fn scroll_for_lazy_content(&self, config: &ScrollConfig) -> Result<(), ScrapeError> {
+        let max_scrolls = config.max_scrolls.unwrap_or(DEFAULT_LAZY_SCROLLS).min(MAX_LAZY_SCROLLS);
+        let delay = Duration::from_millis(config.delay_ms.unwrap_or(1500));
+        let stop_when_idle = config.stop_when_no_new_content.unwrap_or(true);
+        let target = match &config.container {
+            Some(selector) => format!(
+                "document.querySelector({})",
+                serde_json::to_string(selector).unwrap_or_default()
+            ),
+            None => "document.scrollingElement || document.body".to_string(),
+        };
+        let scroll_script = format!(
+            r#"(() => {{
+                const el = {};
+                if (!el) return -1;
+                el.scrollTop = el.scrollHeight;
+                if (el === document.scrollingElement || el === document.body) window.scrollTo(0, el.scrollHeight);
+                return el.scrollHeight;
+            }})()"#,
+            target
+        );
+
+        let mut last_height: i64 = -1;
+        for _ in 0..max_scrolls {
+            let new_height = self.evaluate_value::<i64>(scroll_script.as_str()).await.unwrap_or(0);
+            if new_height < 0 {
+                return Err(ScrapeError::ContentExtraction(format!(
+                    "Scroll container not found: {}",
+                    config.container.as_deref().unwrap_or_default()
+                )));
+            }
+
+            if stop_when_idle && new_height == last_height {
+                break;
+            }
+            last_height = new_height;
+            tokio::time::sleep(delay).await;
+        }
+        let _ = self.page().evaluate("window.scrollTo(0, 0);").await;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        Ok(())
+    }
+
+    async fn scroll_adaptive(&self) -> Result<(), ScrapeError> {
+        let mut last_nodes = self.count_matches("*").await?;
+        let mut idle_rounds = 0;
+        for _ in 0..MAX_ADAPTIVE_SCROLLS {
+            let _ = self
+                .page()
+                .evaluate(
+                    r#"(() => {
+                    window.scrollBy(0, window.innerHeight);
+                    document.querySelectorAll('*').forEach(el => {
+                        if (el.scrollHeight > el.clientHeight + 50 && /(auto|scroll)/.test(getComputedStyle(el).overflowY)) {
+                            el.scrollTop = el.scrollTop + el.clientHeight;
+                        }
+                    });
+                })()"#,
+                )
+                .await;
+            tokio::time::sleep(Duration::from_millis(1200)).await;
+
+            let nodes = self.count_matches("*").await?;
+            if nodes == last_nodes {
+                idle_rounds += 1;
+                if idle_rounds >= 2 {
+                    break;
+                }
+            } else {
+                idle_rounds = 0;
+            }
+            last_nodes = nodes;
+        }
+        let _ = self.page().evaluate("window.scrollTo(0, 0);").await;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        Ok(())
+    }
+
+    async fn count_matches(&self, selector: &str) -> Result<usize, ScrapeError> {
+        let script = format!(
+            "document.querySelectorAll({}).length",
+            serde_json::to_string(selector).unwrap_or_default()
+        );
+        self.evaluate_value(script).await
+    }
+
+    async fn scroll_until_count(&self, target: &ScrollUntil) -> Result<usize, ScrapeError> {
+        let max_scrolls = target.max_scrolls.unwrap_or(20);
+        let mut count = self.count_matches(&target.selector).await?;
+        for _ in 0..max_scrolls {
+            if count >= target.min_count {
+                break;
+            }
+            let _ = self
+                .page()
+                .evaluate("window.scrollTo(0, document.body.scrollHeight);")
+                .await;
+            tokio::time::sleep(Duration::from_millis(1500)).await;
+            count = self.count_matches(&target.selector).await?;
+        }
+        let _ = self.page().evaluate("window.scrollTo(0, 0);").await;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        Ok(count)
+    }
+
+    async fn run_actions(&self, actions: &[Action], default_timeout: Duration) -> Result<(), ScrapeError> {
+        for (index, action) in actions.iter().enumerate() {
+            emit("action", format!("Running action {}: {:?}", index, action));
+            let page = self.page();
+            let outcome = match action {
+                Action::Click { selector } => match click_element(&page, selector).await {
+                    Ok(true) => Ok(()),
+                    Ok(false) => Err(format!("element {} not found", selector)),
+                    Err(e) => Err(e.to_string()),
+                },
+                Action::Type { selector, text } => match type_into_field(&page, selector, text).await {
+                    Ok(true) => Ok(()),
+                    Ok(false) => Err(format!("field {} not found", selector)),
+                    Err(e) => Err(e.to_string()),
+                },
+                Action::WaitForSelector { selector, timeout_ms } => {
+                    let timeout_ms = timeout_ms.unwrap_or(default_timeout.as_millis() as u64);
+                    match wait_for_any_element(&page, None, std::slice::from_ref(selector), timeout_ms).await {
+                        Ok(Some(_)) => Ok(()),
+                        Ok(None) => Err(format!("timed out waiting for {}", selector)),
+                        Err(e) => Err(e.to_string()),
+                    }
+                }
+                Action::WaitMs { ms } => {
+                    tokio::time::sleep(Duration::from_millis((*ms).min(MAX_ACTION_WAIT_MS))).await;
+                    Ok(())
+                }
+                Action::Scroll { selector } => {
+                    let script = match selector {
+                        Some(selector) => format!(
+                            r#"(() => {{
+                                const el = document.querySelector({});
+                                if (!el) return false;
+                                el.scrollIntoView({{ block: 'center' }});
+                                return true;
+                            }})()"#,
+                            serde_json::to_string(selector).unwrap_or_default()
+                        ),
+                        None => "window.scrollBy(0, window.innerHeight); true".to_string(),
+                    };
+                    match self.evaluate_value::<bool>(script).await {
+                        Ok(true) => Ok(()),
+                        Ok(false) => Err(format!("element {} not found", selector.as_deref().unwrap_or_default())),
+                        Err(e) => Err(e.to_string()),
+                    }
+                }
+            };
+            if let Err(message) = outcome {
+                return Err(ScrapeError::ContentExtraction(format!("Action {} failed: {}", index, message)));
+            }
+            tokio::time::sleep(Duration::from_millis(300)).await;
+        }
+        Ok(())
+    }
+
+    async fn run_user_script(&self, script: &str) -> Result<Option<serde_json::Value>, ScrapeError> {
+        let params = EvaluateParams::builder()
+            .expression(script)
+            .await_promise(true)
+            .return_by_value(true)
+            .build()
+            .map_err(ScrapeError::EvaluationFailed)?;
+        let result = self
+            .page()
+            .evaluate_expression(params)
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("pre_extract_js: {}", e)))?;
+        Ok(result.value().cloned())
+    }
+
+    async fn extract_metadata(&self) -> Result<HashMap<String, String>, ScrapeError> {
+        self.evaluate_value(
+            r#"(() => {
+                const metadata = {};
+                document.querySelectorAll('meta[content]').forEach(meta => {
+                    const key = meta.getAttribute('property') || meta.getAttribute('name') || meta.getAttribute('itemprop');
+                    const content = (meta.getAttribute('content') || '').trim();
+                    if (!key || !content) return;
+                    const name = key.trim().toLowerCase();
+                    if (!(name in metadata)) metadata[name] = content;
+                });
+                const canonical = document.querySelector('link[rel="canonical"][href]');
+                if (canonical) metadata['canonical'] = canonical.href;
+                return metadata;
+            })()"#,
+        )
+        .await
+    }
+
+    async fn extract_structured_data(&self) -> Result<Vec<serde_json::Value>, ScrapeError> {
+        let blocks = self
+            .evaluate_value::<Vec<String>>(
+                r#"Array.from(document.querySelectorAll('script[type="application/ld+json"]'))
+                    .map(el => el.textContent || '')
+                    .filter(text => text.trim().length > 0)"#,
+            )
+            .await?;
+        Ok(blocks
+            .iter()
+            .filter_map(|block| match serde_json::from_str(block.trim()) {
+                Ok(value) => Some(value),
+                Err(e) => {
+                    tracing::warn!("Skipping invalid JSON-LD block: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    async fn extract_text(&self, format: OutputFormat) -> Result<Option<String>, ScrapeError> {
+        if format == OutputFormat::Markdown {
+            return self.extract_markdown().await;
+        }
+        self.evaluate_value::<Option<String>>(
+            r#"(() => {
+            const clone = document.body.cloneNode(true);
+            clone.querySelectorAll('script, style, noscript, nav, header, footer, svg, button, input').forEach(el => el.remove());
+            let text = clone.innerText || clone.textContent || '';
+            return text.replace(/\s\s+/g, ' ').trim().substring(0, 100000);
+        })()"#,
+        )
+        .await
+    }
+
+    async fn extract_markdown(&self) -> Result<Option<String>, ScrapeError> {
+        self.evaluate_value::<Option<String>>(
+            r#"(() => {
+            const root = document.querySelector('main, article, [role="main"]') || document.body;
+            const clone = root.cloneNode(true);
+            clone.querySelectorAll('script, style, noscript, nav, header, footer, svg, button, input').forEach(el => el.remove());
+            const squash = text => text.replace(/\s+/g, ' ');
+            const inline = node => {
+                if (node.nodeType === Node.TEXT_NODE) return squash(node.textContent);
+                if (node.nodeType !== Node.ELEMENT_NODE) return '';
+                const content = Array.from(node.childNodes).map(inline).join('');
+                switch (node.tagName) {
+                    case 'A': {
+                        const label = content.trim();
+                        return label && node.href ? `[${label}](${node.href})` : content;
+                    }
+                    case 'STRONG': case 'B': return content.trim() ? `**${content.trim()}**` : '';
+                    case 'EM': case 'I': return content.trim() ? `*${content.trim()}*` : '';
+                    case 'CODE': return content.trim() ? '`' + content.trim() + '`' : '';
+                    case 'BR': return '\n';
+                    case 'IMG': return node.alt ? `![${node.alt}](${node.src})` : '';
+                    default: return content;
+                }
+            };
+            const blocks = [];
+            const walk = (node, depth) => {
+                if (node.nodeType !== Node.ELEMENT_NODE) {
+                    const text = inline(node).trim();
+                    if (text) blocks.push(text);
+                    return;
+                }
+                const tag = node.tagName;
+                if (/^H[1-6]$/.test(tag)) {
+                    const text = inline(node).trim();
+                    if (text) blocks.push('#'.repeat(Number(tag[1])) + ' ' + text);
+                } else if (tag === 'UL' || tag === 'OL') {
+                    const items = Array.from(node.children).filter(child => child.tagName === 'LI');
+                    const lines = [];
+                    items.forEach((item, i) => {
+                        const marker = tag === 'OL' ? `${i + 1}.` : '-';
+                        const own = Array.from(item.childNodes)
+                            .filter(child => !(child.tagName === 'UL' || child.tagName === 'OL'))
+                            .map(inline).join('').trim();
+                        if (own) lines.push('  '.repeat(depth) + marker + ' ' + own);
+                        item.querySelectorAll(':scope > ul, :scope > ol').forEach(nested => {
+                            const before = blocks.length;
+                            walk(nested, depth + 1);
+                            lines.push(...blocks.splice(before));
+                        });
+                    });
+                    if (lines.length) blocks.push(lines.join('\n'));
+                } else if (tag === 'PRE') {
+                    blocks.push('```\n' + node.textContent.trim() + '\n```');
+                } else if (tag === 'BLOCKQUOTE') {
+                    const text = inline(node).trim();
+                    if (text) blocks.push(text.split('\n').map(line => '> ' + line).join('\n'));
+                } else if (tag === 'P' || !Array.from(node.children).some(child => getComputedStyle(child).display !== 'inline')) {
+                    const text = inline(node).trim();
+                    if (text) blocks.push(text);
+                } else {
+                    node.childNodes.forEach(child => walk(child, depth));
+                }
+            };
+            document.body.appendChild(clone);
+            clone.style.display = 'none';
+            try {
+                walk(clone, 0);
+            } finally {
+                clone.remove();
+            }
+            return blocks.join('\n\n').replace(/\n{3,}/g, '\n\n').trim().substring(0, 100000);
+        })()"#,
+        )
+        .await
+    }
+
+    async fn extract_images(
+        &self,
+        min_width: Option<u32>,
+        min_height: Option<u32>,
+    ) -> Result<Vec<ImageData>, ScrapeError> {
+        let images: Vec<ImageData> = self.evaluate_value(
+                r#"(() => {
+                return Array.from(document.querySelectorAll('img')).map(img => {
+                    let src = img.src || img.getAttribute('data-src') || '';
+                    if (src && !src.startsWith('http') && !src.startsWith('data:')) {
+                        try {
+                            src = new URL(src, window.location.href).href;
+                        } catch (e) {
+                            src = '';
+                        }
+                    }
+                    return {
+                        src,
+                        alt: img.alt || '',
+                        width: img.naturalWidth || null,
+                        height: img.naturalHeight || null
+                    };
+                }).filter(img => img.src.startsWith('http'));
+            })()"#,
+        )
+        .await?;
+
+        let large_enough = |size: Option<u32>, min: Option<u32>| match (size, min) {
+            (Some(size), Some(min)) => size >= min,
+            _ => true,
+        };
+        Ok(images
+            .into_iter()
+            .filter(|img| large_enough(img.width, min_width) && large_enough(img.height, min_height))
+            .take(20)
+            .collect())
+    }
+
+    async fn extract_links(&self) -> Result<Vec<LinkData>, ScrapeError> {
+        self.evaluate_value(
+                r#"(() => {
+                return Array.from(document.querySelectorAll('a[href]')).map(link => {
+                    let href = link.href;
+                    return {
+                        href,
+                        text: (link.innerText || '').trim().substring(0, 200),
+                        is_internal: link.host === window.location.host
+                    };
+                }).filter(link => link.href.startsWith('http')).slice(0, 50);
+            })()"#,
+        )
+        .await
+    }
+
+    async fn extract_selectors(
+        &self,
+        rules: &[SelectorRule],
+        warnings: &mut Vec<String>,
+    ) -> HashMap<String, Vec<SelectorMatch>> {
+        let mut results = HashMap::new();
+        for rule in rules {
+            let script = format!(
+                r#"(() => {{
+                try {{
+                    return Array.from(document.querySelectorAll({})).map(el => ({{
+                        text: ((el.{} || '') + '').trim(),
+                        html: el.innerHTML || ''
+                    }})).slice(0, 200);
+                }} catch (e) {{
+                    return [];
+                }}
+            }})()"#,
+                serde_json::to_string(&rule.selector).unwrap_or_default(),
+                rule.text_mode.js_property()
+            );
+            let matches = or_warn(
+                self.evaluate_value::<Vec<SelectorMatch>>(script).await,
+                &format!("selector '{}'", rule.name),
+                warnings,
+            );
+            results.insert(rule.name.clone(), matches);
+        }
+        results
+    }
+
+    async fn extract_fields(
+        &self,
+        fields: &HashMap<String, String>,
+        warnings: &mut Vec<String>,
+    ) -> HashMap<String, Vec<String>> {
+        let mut results = HashMap::new();
+        for (name, spec) in fields {
+            let script = match spec.trim().strip_prefix("xpath:") {
+                Some(expression) => xpath_script(expression.trim()),
+                None => css_script(spec),
+            };
+            let values = or_warn(
+                self.evaluate_value::<Vec<String>>(script).await,
+                &format!("extract '{}'", name),
+                warnings,
+            );
+            results.insert(name.clone(), values);
+        }
+        results
+    }
+
+    async fn extract_tables(&self) -> Result<Vec<TableData>, ScrapeError> {
+        self.evaluate_value(
+            r#"(() => {
+                const expand = row => {
+                    const values = [];
+                    for (const cell of row.cells) {
+                        const text = (cell.innerText || cell.textContent || '').trim();
+                        const span = Math.max(1, Math.min(cell.colSpan || 1, 50));
+                        for (let i = 0; i < span; i++) values.push(text);
+                    }
+                    return values;
+                };
+                return Array.from(document.querySelectorAll('table')).slice(0, 50).map((table, index) => {
+                    const rows = Array.from(table.rows);
+                    let headerRow = table.tHead && table.tHead.rows.length ? table.tHead.rows[0] : null;
+                    if (!headerRow && rows.length && Array.from(rows[0].cells).every(cell => cell.tagName === 'TH')) {
+                        headerRow = rows[0];
+                    }
+                    const headers = [];
+                    if (headerRow) {
+                        const seen = {};
+                        expand(headerRow).forEach((name, i) => {
+                            let key = name || `col_${i}`;
+                            seen[key] = (seen[key] || 0) + 1;
+                            if (seen[key] > 1) key = `${key}_${seen[key]}`;
+                            headers.push(key);
+                        });
+                    }
+                    const body = rows.filter(row => row !== headerRow && !(table.tHead && table.tHead.contains(row)));
+                    const data = body.slice(0, 500).map(row => {
+                        const record = {};
+                        expand(row).forEach((value, i) => {
+                            record[headers[i] || `col_${i}`] = value;
+                        });
+                        return record;
+                    }).filter(record => Object.values(record).some(value => value.length > 0));
+                    const caption = table.caption ? (table.caption.innerText || '').trim() : '';
+                    return { index, caption: caption || null, headers, rows: data };
+                }).filter(table => table.rows.length > 0);
+            })()"#,
+        )
+        .await
+    }
+
+    async fn extract_form_state(&self, selector: &str) -> Result<HashMap<String, String>, ScrapeError> {
+        let script = format!(
+            r#"(() => {{
+                const form = document.querySelector({});
+                if (!form) return {{}};
+                const state = {{}};
+                const fields = form.elements || form.querySelectorAll('input, select, textarea');
+                for (const field of fields) {{
+                    if (!field.name || field.disabled) continue;
+                    const type = (field.type || '').toLowerCase();
+                    if ((type === 'checkbox' || type === 'radio') && !field.checked) continue;
+                    if (type === 'submit' || type === 'button' || type === 'file') continue;
+                    state[field.name] = field.value == null ? '' : String(field.value);
+                }}
+                return state;
+            }})()"#,
+            serde_json::to_string(selector).unwrap_or_default()
+        );
+        self.evaluate_value(script).await
+    }
+
+    async fn extract_noscript(&self) -> Result<Vec<String>, ScrapeError> {
+        self.evaluate_value(
+            r#"(() => {
+                const parser = new DOMParser();
+                return Array.from(document.querySelectorAll('noscript')).map(el => {
+                    const doc = parser.parseFromString(el.textContent || el.innerHTML || '', 'text/html');
+                    const links = Array.from(doc.querySelectorAll('a[href]'))
+                        .map(a => a.getAttribute('href'))
+                        .filter(href => href)
+                        .map(href => {
+                            try { return new URL(href, window.location.href).href; } catch (e) { return href; }
+                        });
+                    let text = (doc.body ? doc.body.textContent : '').replace(/\s\s+/g, ' ').trim();
+                    if (links.length) text = (text + ' ' + links.join(' ')).trim();
+                    return text;
+                }).filter(text => text.length > 0).slice(0, 50);
+            })()"#,
+        )
+        .await
+    }
+
+    async fn extract_faqs(&self) -> Result<Vec<FaqEntry>, ScrapeError> {
+        self.evaluate_value(
+            r#"(() => {
+                const clean = (s) => (s || '').replace(/<[^>]*>/g, ' ').replace(/\s\s+/g, ' ').trim();
+                const fromLd = [];
+                const visit = (node) => {
+                    if (!node || typeof node !== 'object') return;
+                    if (Array.isArray(node)) { node.forEach(visit); return; }
+                    const type = [].concat(node['@type'] || []);
+                    if (type.includes('Question')) {
+                        const answer = [].concat(node.acceptedAnswer || node.suggestedAnswer || [])[0];
+                        if (node.name && answer) {
+                            fromLd.push({ question: clean(node.name), answer: clean(answer.text) });
+                        }
+                    }
+                    Object.values(node).forEach(visit);
+                };
+                document.querySelectorAll('script[type="application/ld+json"]').forEach(el => {
+                    try { visit(JSON.parse(el.textContent)); } catch (e) {}
+                });
+                if (fromLd.length) return fromLd.slice(0, 100);
+
+                const faqs = [];
+                document.querySelectorAll('details').forEach(d => {
+                    const summary = d.querySelector('summary');
+                    if (!summary) return;
+                    const answer = clean(d.textContent).replace(clean(summary.textContent), '').trim();
+                    faqs.push({ question: clean(summary.textContent), answer });
+                });
+                document.querySelectorAll('dl').forEach(dl => {
+                    dl.querySelectorAll('dt').forEach(dt => {
+                        const dd = dt.nextElementSibling;
+                        if (dd && dd.tagName === 'DD') {
+                            faqs.push({ question: clean(dt.textContent), answer: clean(dd.textContent) });
+                        }
+                    });
+                });
+                document.querySelectorAll('.faq-item, .faq, [itemtype*="Question"]').forEach(item => {
+                    const q = item.querySelector('.question, .faq-question, [itemprop="name"], h2, h3, h4, button');
+                    if (!q) return;
+                    const a = item.querySelector('.answer, .faq-answer, [itemprop="acceptedAnswer"], p');
+                    const answer = a ? clean(a.textContent) : clean(item.textContent).replace(clean(q.textContent), '').trim();
+                    faqs.push({ question: clean(q.textContent), answer });
+                });
+                return faqs.filter(f => f.question && f.answer).slice(0, 100);
+            })()"#,
+        )
+        .await
+    }
+
+    async fn extract_dates(&self) -> Result<Vec<DateData>, ScrapeError> {
+        let script = format!(
+            r#"(() => {{
+                const out = [];
+                document.querySelectorAll('time').forEach(el => {{
+                    const raw = el.getAttribute('datetime') || el.textContent;
+                    if (raw && raw.trim()) out.push({{ raw: raw.trim(), source: 'time' }});
+                }});
+                const visit = (node) => {{
+                    if (!node || typeof node !== 'object') return;
+                    if (Array.isArray(node)) {{ node.forEach(visit); return; }}
+                    for (const [key, value] of Object.entries(node)) {{
+                        if (typeof value === 'string' && /date|time/i.test(key) && !/type/i.test(key)) {{
+                            out.push({{ raw: value, source: 'json-ld' }});
+                        }} else {{
+                            visit(value);
+                        }}
+                    }}
+                }};
+                document.querySelectorAll('script[type="application/ld+json"]').forEach(el => {{
+                    try {{ visit(JSON.parse(el.textContent)); }} catch (e) {{}}
+                }});
+                const patterns = [
+                    /\d{{4}}-\d{{2}}-\d{{2}}(?:[T ]\d{{2}}:\d{{2}}(?::\d{{2}})?(?:\.\d+)?(?:Z|[+-]\d{{2}}:?\d{{2}})?)?/g,
+                    ...{}.map(source => new RegExp(source, 'gi')),
+                    /\d{{1,2}}\/\d{{1,2}}\/\d{{4}}/g,
+                    /(?:\d+|an?) (?:second|sec|minute|min|hour|hr|day|week|month|year)s? ago/gi,
+                    /(?:yesterday|today)/gi
+                ];
+                const text = (document.body.innerText || '').substring(0, 200000);
+                for (const pattern of patterns) {{
+                    for (const match of text.matchAll(pattern)) {{
+                        out.push({{ raw: match[0], source: 'text' }});
+                        if (out.length > 1000) return out;
+                    }}
+                }}
+                return out;
+            }})()"#,
+            serde_json::to_string(&month_date_patterns()).unwrap_or_default()
+        );
+        let candidates: Vec<DateCandidate> = self.evaluate_value(script).await?;
+        Ok(normalize_dates(candidates))
+    }
+
+    async fn extract_sections(&self) -> Result<Vec<SectionData>, ScrapeError> {
+        self.evaluate_value(
+            r#"(() => {
+                const tags = ['header', 'nav', 'main', 'article', 'aside', 'footer'];
+                return Array.from(document.querySelectorAll(tags.join(', ')))
+                    .filter(el => !tags.some(t => el.parentElement && el.parentElement.closest(t) && t === el.tagName.toLowerCase()))
+                    .map(el => ({
+                        tag: el.tagName.toLowerCase(),
+                        text: (el.innerText || el.textContent || '').replace(/\s\s+/g, ' ').trim().substring(0, 20000),
+                        link_count: el.querySelectorAll('a[href]').length,
+                        image_count: el.querySelectorAll('img').length
+                    }))
+                    .slice(0, 100);
+            })()"#,
+        )
+        .await
+    }
+
+    pub async fn capture_screenshot(&self, full_page: bool) -> Result<Vec<u8>, ScrapeError> {
+        self.page()
+            .screenshot(
+                ScreenshotParams::builder()
+                    .format(CaptureScreenshotFormat::Png)
+                    .full_page(full_page)
+                    .build(),
+            )
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Screenshot: {}", e)))
+    }
+
+    pub async fn capture_element_screenshot(&self, selector: &str) -> Result<Vec<u8>, ScrapeError> {
+        let script = format!(
+            r#"(async () => {{
+                const el = document.querySelector({});
+                if (!el) return null;
+                el.scrollIntoView({{ block: 'center', inline: 'center' }});
+                await new Promise(r => setTimeout(r, 300));
+                const rect = el.getBoundingClientRect();
+                return [rect.left + window.scrollX, rect.top + window.scrollY, rect.width, rect.height];
+            }})()"#,
+            serde_json::to_string(selector).unwrap_or_default()
+        );
+        let Some((x, y, width, height)) = self.evaluate_value::<Option<(f64, f64, f64, f64)>>(script).await? else {
+            return Err(ScrapeError::ContentExtraction(format!("Screenshot element not found: {}", selector)));
+        };
+        if width < 1.0 || height < 1.0 {
+            return Err(ScrapeError::ContentExtraction(format!("Screenshot element has zero size: {}", selector)));
+        }
+
+        self.page()
+            .screenshot(
+                ScreenshotParams::builder()
+                    .format(CaptureScreenshotFormat::Png)
+                    .clip(Viewport { x, y, width, height, scale: 1.0 })
+                    .capture_beyond_viewport(true)
+                    .build(),
+            )
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Screenshot: {}", e)))
+    }
+
+    async fn render_pdf(&self, options: &PdfOptions) -> Result<Vec<u8>, ScrapeError> {
+        let params = PrintToPdfParams::builder()
+            .landscape(options.landscape)
+            .print_background(options.print_background.unwrap_or(true))
+            .build();
+        self.page()
+            .pdf(params)
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("PDF: {}", e)))
+    }
+
+    async fn find_broken_anchors(&self) -> Result<Vec<String>, ScrapeError> {
+        self.evaluate_value(
+            r##"(() => {
+                const here = window.location.href.split('#')[0];
+                const anchors = new Set();
+                document.querySelectorAll('a[href*="#"]').forEach(a => {
+                    const href = a.getAttribute('href') || '';
+                    const [base, fragment] = href.split('#');
+                    if (!fragment || fragment.startsWith('/') || fragment.startsWith('!')) return;
+                    if (base) {
+                        try {
+                            if (new URL(base, here).href.split('#')[0] !== here) return;
+                        } catch (e) { return; }
+                    }
+                    anchors.add(fragment);
+                });
+                return Array.from(anchors).filter(fragment => {
+                    let id = fragment;
+                    try { id = decodeURIComponent(fragment); } catch (e) {}
+                    if (id.toLowerCase() === 'top') return false;
+                    return !document.getElementById(id) && document.getElementsByName(id).length === 0;
+                }).map(fragment => '#' + fragment);
+            })()"##,
+        )
+        .await
+    }
+
+    async fn extract_computed_styles(
+        &self,
+        request: &ComputedStylesRequest,
+    ) -> Result<Vec<HashMap<String, String>>, ScrapeError> {
+        let script = format!(
+            r#"(() => {{
+                const properties = {};
+                return Array.from(document.querySelectorAll({})).slice(0, 100).map(el => {{
+                    const style = window.getComputedStyle(el);
+                    const values = {{}};
+                    for (const prop of properties) {{
+                        values[prop] = style.getPropertyValue(prop) || '';
+                    }}
+                    return values;
+                }});
+            }})()"#,
+            serde_json::to_string(&request.properties).unwrap_or_else(|_| "[]".to_string()),
+            serde_json::to_string(&request.selector).unwrap_or_default()
+        );
+        self.evaluate_value(script).await
+    }
+
+    async fn extract_products(
+        &self,
+        card_selector: &str,
+        fields: &ProductFields,
+    ) -> Result<Vec<Product>, ScrapeError> {
+        let fields = ProductFields {
+            title: fields.title.clone().or_else(|| Some("h2, h3, h4, [class*='title' i], [class*='name' i]".to_string())),
+            price: fields.price.clone().or_else(|| Some("[class*='price' i], [itemprop='price']".to_string())),
+            image: fields.image.clone().or_else(|| Some("img".to_string())),
+            url: fields.url.clone().or_else(|| Some("a[href]".to_string())),
+        };
+        let script = format!(
+            r#"(() => {{
+                const fields = {};
+                const resolve = (value) => {{
+                    if (!value) return null;
+                    try {{ return new URL(value, window.location.href).href; }} catch (e) {{ return value; }}
+                }};
+                const pick = (card, selector) => {{
+                    if (!selector) return null;
+                    try {{
+                        return card.matches(selector) ? card : card.querySelector(selector);
+                    }} catch (e) {{
+                        return null;
+                    }}
+                }};
+                const text = (el) => {{
+                    const value = el ? (el.innerText || el.textContent || '').replace(/\s\s+/g, ' ').trim() : '';
+                    return value || null;
+                }};
+                return Array.from(document.querySelectorAll({})).slice(0, 500).map(card => {{
+                    const image = pick(card, fields.image);
+                    const link = pick(card, fields.url);
+                    return {{
+                        title: text(pick(card, fields.title)),
+                        price: text(pick(card, fields.price)),
+                        image: image ? resolve(image.currentSrc || image.src || image.getAttribute('data-src')) : null,
+                        url: link ? resolve(link.getAttribute('href')) : null
+                    }};
+                }});
+            }})()"#,
+            serde_json::to_string(&fields).unwrap_or_else(|_| "{}".to_string()),
+            serde_json::to_string(card_selector).unwrap_or_default()
+        );
+        self.evaluate_value(script).await
+    }
+
+    async fn detect_cmp(&self) -> Result<Option<String>, ScrapeError> {
+        self.evaluate_value::<Option<String>>(
+            r#"(() => {
+                if (window.OneTrust || window.OptanonWrapper || document.getElementById('onetrust-consent-sdk')) return 'onetrust';
+                if (window.Cookiebot || window.CookieConsent && window.CookieConsent.consentID || document.getElementById('CybotCookiebotDialog')) return 'cookiebot';
+                if (window.__qc || document.querySelector('.qc-cmp2-container, #qc-cmp2-container')) return 'quantcast';
+                if (window.Didomi || document.getElementById('didomi-host')) return 'didomi';
+                if (window.UC_UI || window.usercentrics || document.getElementById('usercentrics-root')) return 'usercentrics';
+                if (window.truste || document.getElementById('truste-consent-track')) return 'trustarc';
+                if (window._sp_ || document.querySelector('[id^="sp_message_container"]')) return 'sourcepoint';
+                if (window.klaro || document.querySelector('.klaro')) return 'klaro';
+                if (window.__tcfapi) return 'tcf';
+                if (window.__cmp) return 'cmp';
+                return null;
+            })()"#,
+        )
+        .await
+    }
+
+    async fn select_main_image(&self, images: &[ImageData]) -> Option<String> {
+        let og_image = self
+            .evaluate_value::<Option<String>>(
+                r#"(() => {
+                const meta = document.querySelector('meta[property="og:image"], meta[name="twitter:image"]');
+                if (!meta) return null;
+                try {
+                    return new URL(meta.getAttribute('content'), window.location.href).href;
+                } catch (e) {
+                    return null;
+                }
+            })()"#,
+            )
+            .await
+            .ok()
+            .flatten();
+        og_image.or_else(|| {
+            images
+                .iter()
+                .max_by_key(|img| img.width.unwrap_or(0) as u64 * img.height.unwrap_or(0) as u64)
+                .map(|img| img.src.clone())
+        })
+    }
+
+    async fn extract_palette(&self, images: &[ImageData]) -> Result<Option<Vec<String>>, ScrapeError> {
+        let Some(src) = self.select_main_image(images).await else {
+            return Ok(None);
+        };
+        let bytes = fetch_image(&src, self.proxy.as_ref()).await?;
+        let colors = task::spawn_blocking(move || palette(&bytes))
+            .await
+            .map_err(|e| ScrapeError::ContentExtraction(format!("palette: {}", e)))??;
+        Ok(Some(colors).filter(|colors| !colors.is_empty()))
+    }
+
+    async fn extract_landmarks(&self) -> Result<Vec<LandmarkData>, ScrapeError> {
+        self.evaluate_value(
+                r#"(() => {
+                const implicitRoles = {
+                    main: 'main', nav: 'navigation', aside: 'complementary',
+                    header: 'banner', footer: 'contentinfo', section: 'region',
+                    form: 'form', search: 'search'
+                };
+                const landmarkRoles = [
+                    'main', 'navigation', 'banner', 'contentinfo', 'complementary',
+                    'region', 'search', 'form'
+                ];
+                const cssPath = (el) => {
+                    if (el.id) return '#' + CSS.escape(el.id);
+                    const parts = [];
+                    while (el && el.nodeType === 1 && el !== document.documentElement) {
+                        if (el.id) {
+                            parts.unshift('#' + CSS.escape(el.id));
+                            break;
+                        }
+                        let part = el.tagName.toLowerCase();
+                        const siblings = el.parentElement
+                            ? Array.from(el.parentElement.children).filter(s => s.tagName === el.tagName)
+                            : [];
+                        if (siblings.length > 1) {
+                            part += ':nth-of-type(' + (siblings.indexOf(el) + 1) + ')';
+                        }
+                        parts.unshift(part);
+                        el = el.parentElement;
+                    }
+                    return parts.join(' > ');
+                };
+                const selector = landmarkRoles.map(r => '[role="' + r + '"]')
+                    .concat(['main', 'nav', 'aside', 'header', 'footer', 'section', 'search'])
+                    .join(', ');
+                return Array.from(document.querySelectorAll(selector)).map(el => {
+                    const tag = el.tagName.toLowerCase();
+                    const role = el.getAttribute('role') || implicitRoles[tag] || '';
+                    const text = (el.innerText || el.textContent || '').replace(/\s\s+/g, ' ').trim();
+                    return { role, tag, text_length: text.length, selector: cssPath(el) };
+                }).slice(0, 100);
+            })()"#,
+        )
+        .await
+    }
+
+    async fn extract_hidden_text(&self) -> Result<Option<String>, ScrapeError> {
+        self.evaluate_value(
+                r#"(() => {
+                const isHidden = (el) => {
+                    const style = window.getComputedStyle(el);
+                    if (style.display === 'none' || style.visibility === 'hidden') return true;
+                    if (parseFloat(style.opacity) === 0) return true;
+                    const rect = el.getBoundingClientRect();
+                    if (style.position === 'absolute' || style.position === 'fixed') {
+                        if (rect.right < 0 || rect.bottom < 0) return true;
+                        if (rect.left > document.documentElement.scrollWidth) return true;
+                    }
+                    if (style.textIndent && parseFloat(style.textIndent) < -999) return true;
+                    return false;
+                };
+                const skip = new Set(['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE', 'SVG', 'HEAD']);
+                const chunks = [];
+                const walk = (el) => {
+                    for (const child of el.children) {
+                        if (skip.has(child.tagName.toUpperCase())) continue;
+                        if (isHidden(child)) {
+                            const t = (child.textContent || '').replace(/\s\s+/g, ' ').trim();
+                            if (t) chunks.push(t);
+                        } else {
+                            walk(child);
+                        }
+                    }
+                };
+                walk(document.body);
+                return chunks.join(' ').substring(0, 100000);
+            })()"#,
+        )
+        .await
+    }
+
+    async fn start_xhr_capture(
+        &self,
+        patterns: Vec<String>,
+    ) -> Result<(task::JoinHandle<()>, CapturedRequests), ScrapeError> {
+        let mut events = self
+            .page()
+            .event_listener::<EventResponseReceived>()
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Listen Responses: {}", e)))?;
+
+        let captured: CapturedRequests = Arc::new(Mutex::new(Vec::new()));
+        let sink = captured.clone();
+        let handle = task::spawn(async move {
+            while let Some(event) = events.next().await {
+                let response_url = &event.response.url;
+                let is_json = event.response.mime_type.contains("json");
+                if is_json
+                    && patterns.iter().any(|p| response_url.contains(p.as_str()))
+                    && let Ok(mut list) = sink.lock()
+                {
+                    list.push((event.request_id.clone(), response_url.clone(), event.response.status));
+                }
+            }
+        });
+
+        Ok((handle, captured))
+    }
+
+    async fn start_interception(
+        &self,
+        credentials: Option<(String, String)>,
+        blocked_types: Vec<String>,
+    ) -> Result<Interception, ScrapeError> {
+        let page = self.page();
+        let mut paused = page
+            .event_listener::<EventRequestPaused>()
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Listen Paused Requests: {}", e)))?;
+        let mut auth_required = page
+            .event_listener::<EventAuthRequired>()
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Listen Auth Challenges: {}", e)))?;
+
+        let continue_page = page.clone();
+        let paused_handle = task::spawn(async move {
+            while let Some(event) = paused.next().await {
+                if should_block(&event.resource_type, &blocked_types) {
+                    let _ = continue_page
+                        .execute(FailRequestParams::new(event.request_id.clone(), ErrorReason::BlockedByClient))
+                        .await;
+                } else {
+                    let _ = continue_page
+                        .execute(ContinueRequestParams::new(event.request_id.clone()))
+                        .await;
+                }
+            }
+        });
+
+        let auth_page = page.clone();
+        let auth_handle = task::spawn(async move {
+            while let Some(event) = auth_required.next().await {
+                let mut response = AuthChallengeResponse::builder();
+                response = match &credentials {
+                    Some((username, password)) => response
+                        .response(AuthChallengeResponseResponse::ProvideCredentials)
+                        .username(username.clone())
+                        .password(password.clone()),
+                    None => response.response(AuthChallengeResponseResponse::CancelAuth),
+                };
+                let Ok(response) = response.build() else {
+                    continue;
+                };
+                let _ = auth_page
+                    .execute(ContinueWithAuthParams::new(event.request_id.clone(), response))
+                    .await;
+            }
+        });
+
+        page.execute(FetchEnableParams::builder().handle_auth_requests(true).build())
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Enable Fetch: {}", e)))?;
+
+        Ok(Interception { page, handles: vec![paused_handle, auth_handle] })
+    }
+
+    async fn start_title_capture(&self) -> Result<(task::JoinHandle<()>, TitleHistory), ScrapeError> {
+        let mut loaded = self
+            .page()
+            .event_listener::<EventDomContentEventFired>()
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Listen Navigations: {}", e)))?;
+
+        let history: TitleHistory = Arc::new(Mutex::new(Vec::new()));
+        let (page, sink) = (self.page(), history.clone());
+        let handle = task::spawn(async move {
+            while loaded.next().await.is_some() {
+                let title = page.get_title().await.ok().flatten().unwrap_or_default();
+                if let Ok(mut list) = sink.lock() {
+                    push_title(&mut list, title);
+                }
+            }
+        });
+
+        Ok((handle, history))
+    }
+
+    async fn start_blocked_capture(
+        &self,
+    ) -> Result<(Vec<task::JoinHandle<()>>, BlockedRequests), ScrapeError> {
+        let mut sent = self
+            .page()
+            .event_listener::<EventRequestWillBeSent>()
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Listen Requests: {}", e)))?;
+        let mut failed = self
+            .page()
+            .event_listener::<EventLoadingFailed>()
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("Listen Failures: {}", e)))?;
+
+        let urls: Arc<Mutex<HashMap<RequestId, String>>> = Arc::new(Mutex::new(HashMap::new()));
+        let blocked: BlockedRequests = Arc::new(Mutex::new(Vec::new()));
+
+        let (url_sink, mixed_sink) = (urls.clone(), blocked.clone());
+        let sent_handle = task::spawn(async move {
+            while let Some(event) = sent.next().await {
+                let url = event.request.url.clone();
+                if let Some(mixed) = &event.request.mixed_content_type
+                    && mixed.as_ref() != "none"
+                    && let Ok(mut list) = mixed_sink.lock()
+                {
+                    list.push(BlockedRequest {
+                        url: url.clone(),
+                        reason: format!("mixed-content: {}", mixed.as_ref()),
+                    });
+                }
+                if let Ok(mut map) = url_sink.lock() {
+                    map.insert(event.request_id.clone(), url);
+                }
+            }
+        });
+
+        let failed_sink = blocked.clone();
+        let failed_handle = task::spawn(async move {
+            while let Some(event) = failed.next().await {
+                if event.canceled == Some(true) && event.blocked_reason.is_none() {
+                    continue;
+                }
+                let url = urls
+                    .lock()
+                    .ok()
+                    .and_then(|map| map.get(&event.request_id).cloned())
+                    .unwrap_or_default();
+                let reason = match (&event.blocked_reason, &event.cors_error_status) {
+                    (Some(blocked), _) => format!("blocked: {}", blocked.as_ref()),
+                    (None, Some(cors)) => format!("cors: {}", cors.cors_error.as_ref()),
+                    (None, None) => event.error_text.clone(),
+                };
+                if let Ok(mut list) = failed_sink.lock() {
+                    list.push(BlockedRequest { url, reason });
+                }
+            }
+        });
+
+        Ok((vec![sent_handle, failed_handle], blocked))
+    }
+
+    async fn collect_xhr_responses(&self, captured: CapturedRequests) -> Vec<XhrResponseData> {
+        let requests = captured.lock().map(|list| list.clone()).unwrap_or_default();
+        let mut total_bytes = 0;
+        let mut responses = Vec::new();
+
+        for (request_id, url, status) in requests {
+            let Ok(result) = self.page().execute(GetResponseBodyParams::new(request_id)).await else {
+                continue;
+            };
+            if result.base64_encoded {
+                continue;
+            }
+            total_bytes += result.body.len();
+            if total_bytes > MAX_XHR_CAPTURE_BYTES {
+                break;
+            }
+            if let Ok(body) = serde_json::from_str::<serde_json::Value>(&result.body) {
+                responses.push(XhrResponseData { url, status, body });
+            }
+        }
+
+        responses
+    }
+
+    pub async fn new_page(&self) -> Result<Page, ScrapeError> {
+        let browser = self
+            .browser
+            .as_ref()
+            .ok_or_else(|| ScrapeError::PageCreation("Browser already closed".to_string()))?;
+        let page = browser
+            .new_page("about:blank")
+            .await
+            .map_err(|e| ScrapeError::PageCreation(e.to_string()))?;
+        self.setup_evasions(&page, None, &self.user_agent, None).await?;
+        Ok(page)
+    }
+
+    pub async fn goto(&self, url: &str) -> Result<(), ScrapeError> {
+        self.page()
+            .goto(url)
+            .await
+            .map_err(|e| ScrapeError::Navigation(format!("Failed to navigate: {}", e)))?;
+        self.wait_for_body(TimeoutConfig::default().body_wait()).await
+    }
+
+    pub async fn execute_raw(&self, command: RawCommand) -> Result<serde_json::Value, ScrapeError> {
+        self.page()
+            .execute(command)
+            .await
+            .map(|response| response.result)
+            .map_err(|e| ScrapeError::EvaluationFailed(format!("CDP command: {}", e)))
+    }
+
+    pub async fn is_authenticated(&self, url: &str, credentials: &LoginCredentials) -> bool {
+        if self.page().goto(url).await.is_err() {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(2000)).await;
+        let platform = credentials.platform.as_deref().unwrap_or("generic");
+        check_authenticated(&self.page(), platform, credentials).await.unwrap_or(false)
+    }
+
+    pub async fn crawl(
+        &self,
+        req: &ScrapeRequest,
+        options: &CrawlOptions,
+        mut on_result: impl FnMut(&str, Result<ScrapedData, ScrapeError>),
+    ) -> Result<(), ScrapeError> {
+        let compile = |pattern: &Option<String>| {
+            pattern
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .map_err(|e| ScrapeError::InvalidRequest(format!("Invalid crawl filter: {}", e)))
+        };
+        let include = compile(&options.url_filter)?;
+        let exclude = compile(&options.exclude_filter)?;
+        let max_depth = options.max_depth.unwrap_or(DEFAULT_CRAWL_DEPTH);
+        let max_pages = options.max_pages.unwrap_or(DEFAULT_CRAWL_PAGES).min(MAX_CRAWL_PAGES);
+        let same_domain_only = options.same_domain_only.unwrap_or(true);
+        let root_host = reqwest::Url::parse(&req.url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+            .ok_or_else(|| ScrapeError::InvalidRequest(format!("Invalid crawl url: {}", req.url)))?;
+
+        let mut visited = HashSet::from([crawl_key(&req.url)]);
+        let mut queue = VecDeque::from([(req.url.clone(), 0usize)]);
+        let mut crawled = 0;
+        while let Some((url, depth)) = queue.pop_front() {
+            if crawled >= max_pages {
+                break;
+            }
+            crawled += 1;
+            emit("status", format!("Crawling {} (depth {})", url, depth));
+
+            let page_req = ScrapeRequest { url: url.clone(), urls: None, crawl: None, ..req.clone() };
+            let result = self.scrape(&page_req).await;
+            if let (Ok(data), true) = (&result, depth < max_depth) {
+                for link in &data.links {
+                    let Ok(parsed) = reqwest::Url::parse(&link.href) else {
+                        continue;
+                    };
+                    if !matches!(parsed.scheme(), "http" | "https") {
+                        continue;
+                    }
+                    let host = parsed.host_str().map(str::to_ascii_lowercase).unwrap_or_default();
+                    if same_domain_only && host != root_host {
+                        continue;
+                    }
+                    if include.as_ref().is_some_and(|re| !re.is_match(&link.href))
+                        || exclude.as_ref().is_some_and(|re| re.is_match(&link.href))
+                    {
+                        continue;
+                    }
+                    if visited.insert(crawl_key(&link.href)) {
+                        queue.push_back((link.href.clone(), depth + 1));
+                    }
+                }
+            }
+            on_result(&url, result);
+        }
+        Ok(())
+    }
+
+    pub async fn render(&self, req: &ScrapeRequest) -> Result<String, ScrapeError> {
+        let started = std::time::Instant::now();
+        let result = self.render_page(req).await;
+        metrics::record_scrape(&result, req.login.is_some(), started.elapsed());
+        result
+    }
+
+    async fn render_page(&self, req: &ScrapeRequest) -> Result<String, ScrapeError> {
+        let url = req.url.as_str();
+        let timeouts = req.timeouts.clone().unwrap_or_default();
+        if let Ok(mut active) = self.active_page.lock() {
+            *active = None;
+        }
+
+        let device = device_profile(req.device.as_deref())?;
+        let user_agent = self.user_agent_for(req, device);
+        self.setup_evasions(&self.page(), req.evasions.as_deref(), user_agent, device).await?;
+        if req.ignore_https_errors {
+            self.page()
+                .execute(SetIgnoreCertificateErrorsParams::new(true))
+                .await
+                .map_err(|e| ScrapeError::EvaluationFailed(format!("Ignore Certificate Errors: {}", e)))?;
+        }
+        let _interception = match &self.proxy_auth {
+            Some(credentials) => Some(self.start_interception(Some(credentials.clone()), Vec::new()).await?),
+            None => None,
+        };
+
+        if let Some(credentials) = &req.login {
+            match auto_login(&self.page(), credentials, url).await {
+                Ok((_, _, Some(true), _)) => return Err(ScrapeError::TwoFactorAuthRequired),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Login before render failed: {}", e),
+            }
+        }
+
+        if land_on_target(&TargetNavigation::new(self, req, &timeouts), url).await? {
+            tokio::time::sleep(timeouts.idle()).await;
+        }
+        self.wait_for_body(timeouts.body_wait()).await?;
+
+        if let Some(selector) = &req.wait_for_selector {
+            let found = wait_for_any_element(
+                &self.page(),
+                None,
+                std::slice::from_ref(selector),
+                timeouts.body_wait().as_millis() as u64,
+            )
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(e.to_string()))?;
+            if found.is_none() {
+                return Err(ScrapeError::ContentExtraction(format!(
+                    "Timed out waiting for selector {}",
+                    selector
+                )));
+            }
+        }
+
+        self.scroll_for_lazy_content(&req.scroll.clone().unwrap_or_default()).await?;
+        self.evaluate_value("document.documentElement.outerHTML").await
+    }
+
+    pub async fn scrape(&self, req: &ScrapeRequest) -> Result<ScrapedData, ScrapeError> {
+        let started = std::time::Instant::now();
+        let result = self.scrape_with_retries(req).await;
+        metrics::record_scrape(&result, req.login.is_some(), started.elapsed());
+        result
+    }
+
+    async fn scrape_with_retries(&self, req: &ScrapeRequest) -> Result<ScrapedData, ScrapeError> {
+        let max_retries = req.max_retries.unwrap_or(0).min(MAX_SCRAPE_RETRIES);
+        let setup = self.prepare_scrape(req).await?;
+        let mut attempt = 0;
+        loop {
+            match self.scrape_attempt(req, &setup).await {
+                Ok(data) => {
+                    if attempt > 0 {
+                        tracing::info!("Scrape of {} succeeded on attempt {}", req.url, attempt + 1);
+                    }
+                    return Ok(data);
+                }
+                Err(e) if e.is_transient() && attempt < max_retries => {
+                    let delay = Duration::from_millis(RETRY_BASE_DELAY_MS << attempt);
+                    attempt += 1;
+                    tracing::warn!(
+                        "Scrape attempt {} of {} failed: {}, retrying in {}ms",
+                        attempt,
+                        max_retries + 1,
+                        e,
+                        delay.as_millis()
+                    );
+                    emit("navigation", format!("Retrying after error: {}", e));
+                    tokio::time::sleep(delay).await;
+                    if let Ok(mut active) = self.active_page.lock() {
+                        *active = None;
+                    }
+                    if let Err(e) = self.page.goto("about:blank").await {
+                        tracing::warn!("Failed to reset page before retry: {}", e);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn prepare_scrape(&self, req: &ScrapeRequest) -> Result<ScrapeSetup, ScrapeError> {
+        let url = req.url.as_str();
+        if let Ok(mut active) = self.active_page.lock() {
+            *active = None;
+        }
+
+        if req.pre_extract_js.is_some() && !custom_js_enabled() {
+            return Err(ScrapeError::InvalidRequest("pre_extract_js requires ENABLE_CUSTOM_JS".to_string()));
+        }
+
+        if let Some(referer) = &req.referer
+            && !is_plausible_url(referer)
+        {
+            return Err(ScrapeError::InvalidRequest(format!("Invalid referer: {}", referer)));
+        }
+
+        let device = device_profile(req.device.as_deref())?;
+        let user_agent = self.user_agent_for(req, device);
+        if req.javascript_enabled() {
+            self.setup_evasions(&self.page(), req.evasions.as_deref(), user_agent, device).await?;
+        } else {
+            self.setup_evasions(&self.page(), Some(&[]), user_agent, device).await?;
+            self.page()
+                .execute(SetScriptExecutionDisabledParams::new(true))
+                .await
+                .map_err(|e| ScrapeError::EvaluationFailed(format!("Disable JavaScript: {}", e)))?;
+        }
+
+        if req.ignore_https_errors {
+            self.page()
+                .execute(SetIgnoreCertificateErrorsParams::new(true))
+                .await
+                .map_err(|e| ScrapeError::EvaluationFailed(format!("Ignore Certificate Errors: {}", e)))?;
+        }
+
+        let blocked_types = req.block_resources.clone().unwrap_or_default();
+        if let Some(kind) = blocked_types.iter().find(|kind| !BLOCKABLE_RESOURCES.contains(&kind.as_str())) {
+            return Err(ScrapeError::InvalidRequest(format!("Unknown resource type: {}", kind)));
+        }
+        let _interception = if self.proxy_auth.is_some() || !blocked_types.is_empty() {
+            Some(self.start_interception(self.proxy_auth.clone(), blocked_types.clone()).await?)
+        } else {
+            None
+        };
+        let images_blocked = blocked_types.iter().any(|kind| kind == "image");
+
+        if req.bypass_cache {
+            self.page()
+                .execute(SetCacheDisabledParams::new(true))
+                .await
+                .map_err(|e| ScrapeError::EvaluationFailed(format!("Disable Cache: {}", e)))?;
+        }
+
+        let warmup_performed = match &req.warmup_url {
+            Some(warmup_url) => {
+                if !is_plausible_url(warmup_url) {
+                    return Err(ScrapeError::InvalidRequest(format!("Invalid warmup_url: {}", warmup_url)));
+                }
+                emit("navigation", format!("Warming up on {}", warmup_url));
+                self.stealth_navigate(warmup_url).await?;
+                true
+            }
+            None => false,
+        };
+
+        let mut warnings = Vec::new();
+        let (login_attempted, login_success, platform_detected, requires_2fa, login_frame) =
+            if let Some(credentials) = req.login.clone() {
+                match auto_login(&self.page(), &credentials, url).await {
+                    Ok((success, platform, tfa, frame)) => {
+                        if tfa.unwrap_or(false) {
+                            return Err(ScrapeError::TwoFactorAuthRequired);
+                        }
+                        (true, Some(success), platform, tfa, frame)
+                    }
+                    Err(e) => {
+                        let error = ScrapeError::LoginFailed(e.to_string());
+                        tracing::warn!("{}", error);
+                        warnings.push(format!("login: {}", error));
+                        (true, Some(false), None, None, None)
+                    }
+                }
+            } else {
+                (false, None, None, None, None)
+            };
+
+        Ok(ScrapeSetup {
+            _interception,
+            images_blocked,
+            warmup_performed,
+            login_attempted,
+            login_success,
+            platform_detected,
+            requires_2fa,
+            login_frame,
+            warnings,
+        })
+    }
+
+    async fn scrape_attempt(&self, req: &ScrapeRequest, setup: &ScrapeSetup) -> Result<ScrapedData, ScrapeError> {
+        let url = req.url.as_str();
+        let timeouts = req.timeouts.clone().unwrap_or_default();
+        if let Ok(mut active) = self.active_page.lock() {
+            *active = None;
+        }
+
+        let xhr_capture = match &req.capture_xhr {
+            Some(patterns) if !patterns.is_empty() => Some(self.start_xhr_capture(patterns.clone()).await?),
+            _ => None,
+        };
+
+        let blocked_capture = if req.report_blocked_requests {
+            Some(self.start_blocked_capture().await?)
+        } else {
+            None
+        };
+
+        let action_follows_tabs = req.search.as_ref().and_then(|search| search.follow_new_tab) == Some(true);
+        let tab_watch = if req.follow_new_tabs || action_follows_tabs {
+            Some(self.start_tab_watch().await?)
+        } else {
+            None
+        };
+
+        let title_capture = if req.capture_title_history {
+            Some(self.start_title_capture().await?)
+        } else {
+            None
+        };
+
+        let mut warnings = setup.warnings.clone();
+        let (images_blocked, warmup_performed) = (setup.images_blocked, setup.warmup_performed);
+        let (login_attempted, login_success, requires_2fa) =
+            (setup.login_attempted, setup.login_success, setup.requires_2fa);
+        let (platform_detected, login_frame) = (setup.platform_detected.clone(), setup.login_frame.clone());
+
+        let max_redirects = req.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS);
+        let handoff = TargetNavigation::new(self, req, &timeouts);
+        let mut redirect_chain = if land_on_target(&handoff, url).await? {
+            tokio::time::sleep(timeouts.idle()).await;
+            handoff.into_chain()
+        } else {
+            Vec::new()
+        };
+        self.wait_for_body(timeouts.body_wait()).await?;
+
+        if req.follow_redirects {
+            self.follow_client_redirects(&mut redirect_chain, max_redirects, timeouts.body_wait())
+                .await?;
+        }
+
+        let framework = if req.detect_framework && req.javascript_enabled() {
+            self.detect_framework().await
+        } else {
+            None
+        };
+        if let Some(name) = &framework {
+            self.wait_for_framework(name).await;
+        }
+
+        if let Some(stable) = &req.wait_for_dom_stable {
+            self.wait_for_dom_stable(stable).await?;
+        }
+
+        if let Some(wait) = &req.wait_for_text {
+            self.wait_for_text(wait).await?;
+        }
+
+        if let Some(selector) = &req.wait_for_selector {
+            emit("navigation", format!("Waiting for {}", selector));
+            let found = wait_for_any_element(
+                &self.page(),
+                None,
+                std::slice::from_ref(selector),
+                timeouts.body_wait().as_millis() as u64,
+            )
+            .await
+            .map_err(|e| ScrapeError::EvaluationFailed(e.to_string()))?;
+            if found.is_none() {
+                return Err(ScrapeError::ContentExtraction(format!(
+                    "Timed out waiting for selector {}",
+                    selector
+                )));
+            }
+        }
+
+        if req.spa_route
+            && let Some((_, fragment)) = url.split_once('#')
+        {
+            self.trigger_spa_route(fragment).await?;
+            self.wait_for_body(timeouts.body_wait()).await?;
+        }
+
+        if let Some(search) = &req.search {
+            let opened_before = tab_watch
+                .as_ref()
+                .and_then(|(_, opened)| opened.lock().ok().map(|list| list.len()))
+                .unwrap_or(0);
+            self.run_search(search).await?;
+            self.wait_for_body(timeouts.body_wait()).await?;
+            if let Some((_, opened)) = &tab_watch
+                && let Ok(mut list) = opened.lock()
+            {
+                match search.follow_new_tab {
+                    Some(false) => list.truncate(opened_before),
+                    Some(true) if !req.follow_new_tabs => {
+                        let n = opened_before.min(list.len());
+                        list.drain(..n);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let followed_tab = match tab_watch {
+            Some((handle, opened)) => {
+                handle.abort();
+                self.switch_to_new_tab(&opened, timeouts.body_wait()).await?
+            }
+            None => None,
+        };
+        
+        if let Some(max_nodes) = req.max_dom_nodes {
+            let node_count = self.count_matches("*").await?;
+            if node_count > max_nodes {
+                return Err(ScrapeError::ContentExtraction(format!(
+                    "page too large: {} DOM nodes exceeds limit of {}",
+                    node_count, max_nodes
+                )));
+            }
+        }
+
+        if let Some(actions) = &req.actions {
+            self.run_actions(actions, timeouts.body_wait()).await?;
+        }
+
+        let script_result = match &req.pre_extract_js {
+            Some(script) => {
+                emit("extraction", "Running pre-extraction script");
+                self.run_user_script(script).await?
+            }
+            None => None,
+        };
+
+        emit("scroll", "Loading lazy content");
+        let matched_count = match &req.scroll_until {
+            _ if !req.javascript_enabled() => None,
+            Some(target) => Some(self.scroll_until_count(target).await?),
+            None => {
+                match req.scroll_strategy.unwrap_or_default() {
+                    ScrollStrategy::None => {}
+                    ScrollStrategy::Fixed => {
+                        self.scroll_for_lazy_content(&req.scroll.clone().unwrap_or_default()).await?
+                    }
+                    ScrollStrategy::Adaptive => self.scroll_adaptive().await?,
+                }
+                None
+            }
+        };
+
+        emit("extraction", "Extracting page content");
+
+        let title = self.page().get_title().await.ok().flatten();
+
+        let title_history = title_capture.map(|(handle, history)| {
+            handle.abort();
+            let mut list = history.lock().map(|list| list.clone()).unwrap_or_default();
+            push_title(&mut list, title.clone().unwrap_or_default());
+            list
+        });
+
+        let metadata = or_warn(self.extract_metadata().await, "metadata", &mut warnings);
+        let structured_data =
+            or_warn(self.extract_structured_data().await, "structured_data", &mut warnings);
+
+        let description = or_warn(
+            self.evaluate_value::<Option<String>>(r#"
+                (() => {
                     const meta = document.querySelector('meta[name="description"]');
                     return meta ? meta.getAttribute('content') : null;
                 })()
             "#)
-            .await
-            .ok()
-            .and_then(|v| v.into_value::<Option<String>>().ok())
-            .flatten();
+            .await,
+            "description",
+            &mut warnings,
+        );
 
-        let text = self
-            .page
-            .evaluate(
-                r#"(() => {
-                const clone = document.body.cloneNode(true);
-                clone.querySelectorAll('script, style, noscript, nav, header, footer, svg, button, input').forEach(el => el.remove());
-                let text = clone.innerText || clone.textContent || '';
-                return text.replace(/\s\s+/g, ' ').trim().substring(0, 100000);
-            })()"#,
+        let output_format = req.output_format.unwrap_or_default();
+        let mut text = or_warn(self.extract_text(output_format).await, "text", &mut warnings);
+
+        let min_text_length = req.min_text_length.unwrap_or(DEFAULT_MIN_TEXT_LENGTH);
+        let is_sparse = |text: &Option<String>| {
+            text.as_deref().map(|t| t.chars().count()).unwrap_or(0) < min_text_length
+        };
+        let extraction_retried = req.empty_content_retry && is_sparse(&text);
+        if extraction_retried {
+            emit("extraction", "Content looks empty, retrying extraction");
+            tokio::time::sleep(Duration::from_millis(1500)).await;
+            self.wait_for_body(timeouts.body_wait()).await?;
+            text = or_warn(self.extract_text(output_format).await, "text", &mut warnings);
+        }
+
+        let (images, links) = if req.text_only {
+            (Vec::new(), Vec::new())
+        } else if images_blocked {
+            (Vec::new(), or_warn(self.extract_links().await, "links", &mut warnings))
+        } else {
+            (
+                or_warn(self.extract_images(req.min_image_width, req.min_image_height).await, "images", &mut warnings),
+                or_warn(self.extract_links().await, "links", &mut warnings),
             )
-            .await
-            .ok()
-            .and_then(|v| v.into_value::<Option<String>>().ok())
-            .flatten();
+        };
 
-        let images = self
-            .page
-            .evaluate(
-                r#"(() => {
-                return Array.from(document.querySelectorAll('img')).map(img => {
-                    let src = img.src || img.getAttribute('data-src') || '';
-                    if (src && !src.startsWith('http') && !src.startsWith('data:')) {
-                        try {
-                            src = new URL(src, window.location.href).href;
-                        } catch (e) {
-                            src = '';
-                        }
-                    }
-                    return { src, alt: img.alt || '' };
-                }).filter(img => img.src.startsWith('http')).slice(0, 20);
-            })()"#,
+        let links = if req.strip_tracking_params {
+            let extra = req.extra_tracking_params.as_deref().unwrap_or(&[]);
+            let mut seen = HashSet::new();
+            links
+                .into_iter()
+                .map(|link| LinkData { href: strip_tracking_params(&link.href, extra), ..link })
+                .filter(|link| seen.insert(link.href.clone()))
+                .collect()
+        } else {
+            links
+        };
+
+        let new_links = req.seen_hrefs.as_ref().map(|seen| {
+            let seen: HashSet<&str> = seen.iter().map(|href| href.as_str()).collect();
+            links
+                .iter()
+                .filter(|link| !seen.contains(link.href.as_str()))
+                .cloned()
+                .collect::<Vec<_>>()
+        });
+
+        let text_direction = or_warn(
+            self.evaluate_value::<Option<String>>(r#"
+                (() => {
+                    const dir = document.documentElement.getAttribute('dir')
+                        || (document.body && document.body.getAttribute('dir'));
+                    if (dir) return dir.toLowerCase();
+                    return document.body ? window.getComputedStyle(document.body).direction : null;
+                })()
+            "#)
+            .await,
+            "text_direction",
+            &mut warnings,
+        );
+
+        let script = text.as_deref().and_then(detect_script);
+
+        let hashed_html = if req.hash_html {
+            or_warn(
+                self.evaluate_value::<Option<String>>("document.documentElement.outerHTML").await,
+                "content_hash",
+                &mut warnings,
             )
-            .await
-            .ok()
-            .and_then(|v| v.into_value::<Vec<ImageData>>().ok())
-            .unwrap_or_default();
+        } else {
+            None
+        };
+        let content_hash = content_hash(text.as_deref().unwrap_or_default(), hashed_html.as_deref());
 
-        let links = self
-            .page
-            .evaluate(
-                r#"(() => {
-                return Array.from(document.querySelectorAll('a[href]')).map(link => {
-                    let href = link.href;
-                    return { href, text: (link.innerText || '').trim().substring(0, 200) };
-                }).filter(link => link.href.startsWith('http')).slice(0, 50);
-            })()"#,
+        let (language, language_confidence) = if req.detect_language {
+            let html_lang = or_warn(
+                self.evaluate_value::<Option<String>>("document.documentElement.getAttribute('lang')")
+                    .await,
+                "language",
+                &mut warnings,
+            );
+            detect_language(html_lang.as_deref(), text.as_deref().unwrap_or_default())
+                .map(|(lang, confidence)| (Some(lang), Some(confidence)))
+                .unwrap_or_default()
+        } else {
+            (None, None)
+        };
+
+        let text_stats = if req.text_stats {
+            Some(compute_text_stats(text.as_deref().unwrap_or_default()))
+        } else {
+            None
+        };
+
+        let hidden_text = if req.detect_hidden_text {
+            or_warn(self.extract_hidden_text().await, "hidden_text", &mut warnings)
+        } else {
+            None
+        };
+
+        let blocked_requests = blocked_capture.map(|(handles, blocked)| {
+            handles.iter().for_each(|h| h.abort());
+            blocked.lock().map(|list| list.clone()).unwrap_or_default()
+        });
+
+        let xhr_responses = match xhr_capture {
+            Some((handle, captured)) => {
+                handle.abort();
+                Some(self.collect_xhr_responses(captured).await)
+            }
+            None => None,
+        };
+
+        let mut selector_results = match &req.selectors {
+            Some(rules) => Some(self.extract_selectors(rules, &mut warnings).await),
+            None => None,
+        };
+        if req.sanitize_html {
+            for selector_match in selector_results.iter_mut().flat_map(|r| r.values_mut()).flatten() {
+                selector_match.html = ammonia::clean(&selector_match.html);
+            }
+        }
+
+        let extracted = match &req.extract {
+            Some(fields) => self.extract_fields(fields, &mut warnings).await,
+            None => HashMap::new(),
+        };
+
+        let tables = if req.extract_tables {
+            or_warn(self.extract_tables().await, "tables", &mut warnings)
+        } else {
+            Vec::new()
+        };
+
+        let form_state = match &req.extract_form_state {
+            Some(selector) => Some(or_warn(self.extract_form_state(selector).await, "form_state", &mut warnings)),
+            None => None,
+        };
+
+        let cookies_set = if req.capture_set_cookies {
+            Some(or_warn(
+                get_cookies(&self.page())
+                    .await
+                    .map_err(|e| ScrapeError::EvaluationFailed(e.to_string())),
+                "cookies_set",
+                &mut warnings,
+            ))
+        } else {
+            None
+        };
+
+        let cookies = if req.return_cookies && login_success != Some(false) {
+            Some(or_warn(
+                get_all_cookies(&self.page())
+                    .await
+                    .map_err(|e| ScrapeError::EvaluationFailed(e.to_string())),
+                "cookies",
+                &mut warnings,
+            ))
+        } else {
+            None
+        };
+
+        let noscript_content = if req.include_noscript {
+            Some(or_warn(self.extract_noscript().await, "noscript", &mut warnings))
+        } else {
+            None
+        };
+
+        let faqs = if req.extract_faqs {
+            Some(or_warn(self.extract_faqs().await, "faqs", &mut warnings))
+        } else {
+            None
+        };
+
+        let dates = if req.extract_dates {
+            Some(or_warn(self.extract_dates().await, "dates", &mut warnings))
+        } else {
+            None
+        };
+
+        let sections = if req.semantic_sections {
+            Some(or_warn(self.extract_sections().await, "sections", &mut warnings))
+        } else {
+            None
+        };
+
+        let palette = if req.extract_colors {
+            or_warn(self.extract_palette(&images).await, "colors", &mut warnings)
+        } else {
+            None
+        };
+        let dominant_color = palette.as_ref().and_then(|colors| colors.first().cloned());
+
+        let screenshot = if let Some(selector) = &req.screenshot_selector {
+            Some(BASE64.encode(self.capture_element_screenshot(selector).await?))
+        } else if req.include_screenshot {
+            or_warn(
+                self.capture_screenshot(true).await.map(|png| Some(BASE64.encode(png))),
+                "screenshot",
+                &mut warnings,
             )
-            .await
-            .ok()
-            .and_then(|v| v.into_value::<Vec<LinkData>>().ok())
-            .unwrap_or_default();
+        } else {
+            None
+        };
+
+        let pdf = if req.pdf {
+            or_warn(
+                self.render_pdf(&req.pdf_options.clone().unwrap_or_default())
+                    .await
+                    .map(|bytes| Some(BASE64.encode(bytes))),
+                "pdf",
+                &mut warnings,
+            )
+        } else {
+            None
+        };
+
+        let broken_anchors = if req.check_anchors {
+            Some(or_warn(self.find_broken_anchors().await, "broken_anchors", &mut warnings))
+        } else {
+            None
+        };
+
+        let computed_styles = match &req.computed_styles {
+            Some(request) => Some(or_warn(
+                self.extract_computed_styles(request).await,
+                "computed_styles",
+                &mut warnings,
+            )),
+            None => None,
+        };
+
+        let products = match &req.product_selector {
+            Some(selector) => Some(or_warn(
+                self.extract_products(selector, &req.product_fields.clone().unwrap_or_default())
+                    .await,
+                "products",
+                &mut warnings,
+            )),
+            None => None,
+        };
+
+        let cmp = if req.detect_cmp && req.javascript_enabled() {
+            or_warn(self.detect_cmp().await, "cmp", &mut warnings)
+        } else {
+            None
+        };
+
+        let landmarks = if req.extract_landmarks {
+            Some(or_warn(self.extract_landmarks().await, "landmarks", &mut warnings))
+        } else {
+            None
+        };
 
         Ok(ScrapedData {
             title,
@@ -278,25 +2925,636 @@ impl Scraper {
             login_success,
             platform_detected,
             requires_2fa,
+            landmarks,
+            hidden_text,
+            xhr_responses,
+            matched_count,
+            selector_results,
+            text_direction,
+            script,
+            extraction_warnings: warnings,
+            session_refreshed: false,
+            form_state,
+            cookies_set,
+            noscript_content,
+            faqs,
+            redirect_chain,
+            dates,
+            framework,
+            sections,
+            screenshot,
+            blocked_requests,
+            broken_anchors,
+            new_links,
+            computed_styles,
+            products,
+            warmup_performed,
+            cmp,
+            login_frame,
+            title_history,
+            text_stats,
+            followed_tab,
+            extraction_retried,
+            dominant_color,
+            palette,
+            proxy_used: None,
+            pdf,
+            metadata,
+            structured_data,
+            extracted,
+            tables,
+            cookies,
+            script_result,
+            language,
+            language_confidence,
+            content_hash,
         })
     }
 }
 
 impl Drop for Scraper {
     fn drop(&mut self) {
+        if !self.owns_browser {
+            let mut pages = vec![self.page.clone()];
+            pages.extend(self.active_page.get_mut().ok().and_then(|active| active.take()));
+            let checkin = match (self.lease.take(), self.browser.take(), self.handler_handle.take()) {
+                (Some(lease), Some(browser), Some(handler)) => Some((browser, handler, lease)),
+                _ => None,
+            };
+            tokio::spawn(async move {
+                for page in pages {
+                    let _ = page.close().await;
+                }
+                if let Some((browser, handler, lease)) = checkin {
+                    pool::checkin(browser, handler, lease).await;
+                }
+            });
+            return;
+        }
         if let Some(mut browser) = self.browser.take() {
             tokio::spawn(async move {
                 let _ = browser.close().await;
                 let _ = browser.wait().await;
+                shutdown::unregister(browser.websocket_address());
             });
         }
     }
 }
 
-pub async fn do_scrape(
-    url: &str,
-    login: Option<LoginCredentials>,
-) -> Result<ScrapedData, ScrapeError> {
+fn is_plausible_url(value: &str) -> bool {
+    let rest = match value.split_once("://") {
+        Some(("http", rest)) | Some(("https", rest)) => rest,
+        _ => return false,
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    !host.is_empty() && !host.contains(char::is_whitespace)
+}
+
+fn strip_tracking_params(href: &str, extra: &[String]) -> String {
+    let (without_fragment, fragment) = match href.split_once('#') {
+        Some((base, fragment)) => (base, Some(fragment)),
+        None => (href, None),
+    };
+    let Some((base, query)) = without_fragment.split_once('?') else {
+        return href.to_string();
+    };
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| {
+            let key = pair.split_once('=').map(|(key, _)| key).unwrap_or(pair).to_ascii_lowercase();
+            !(TRACKING_PARAM_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+                || TRACKING_PARAMS.contains(&key.as_str())
+                || extra.iter().any(|param| param.eq_ignore_ascii_case(&key)))
+        })
+        .collect();
+    let mut cleaned = base.to_string();
+    if !kept.is_empty() {
+        cleaned.push('?');
+        cleaned.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        cleaned.push('#');
+        cleaned.push_str(fragment);
+    }
+    cleaned
+}
+
+fn or_warn<T: Default>(result: Result<T, ScrapeError>, name: &str, warnings: &mut Vec<String>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            warnings.push(format!("{}: {}", name, e));
+            T::default()
+        }
+    }
+}
+
+fn month_date_patterns() -> Vec<String> {
+    vec![
+        format!(r"\b(?:{})\.? \d{{1,2}}(?:st|nd|rd|th)?,? \d{{4}}\b", DATE_MONTHS),
+        format!(r"\b\d{{1,2}} (?:{})\.? \d{{4}}\b", DATE_MONTHS),
+    ]
+}
+
+fn css_script(spec: &str) -> String {
+    let (selector, attribute) = split_attribute(spec);
+    format!(
+        r#"(() => {{
+            try {{
+                const attribute = {};
+                return Array.from(document.querySelectorAll({}))
+                    .map(el => attribute ? el.getAttribute(attribute) : (el.innerText || el.textContent))
+                    .filter(value => value != null)
+                    .map(value => String(value).trim())
+                    .slice(0, 200);
+            }} catch (e) {{
+                return [];
+            }}
+        }})()"#,
+        serde_json::to_string(&attribute).unwrap_or_else(|_| "null".to_string()),
+        serde_json::to_string(selector).unwrap_or_default()
+    )
+}
+
+fn xpath_script(expression: &str) -> String {
+    format!(
+        r#"(() => {{
+            try {{
+                const result = document.evaluate({}, document, null, XPathResult.ANY_TYPE, null);
+                switch (result.resultType) {{
+                    case XPathResult.NUMBER_TYPE:
+                        return Number.isNaN(result.numberValue) ? [] : [String(result.numberValue)];
+                    case XPathResult.STRING_TYPE:
+                        return [result.stringValue.trim()];
+                    case XPathResult.BOOLEAN_TYPE:
+                        return [String(result.booleanValue)];
+                    default: {{
+                        const values = [];
+                        let node = result.iterateNext();
+                        while (node && values.length < 200) {{
+                            const value = node.nodeType === Node.ELEMENT_NODE
+                                ? (node.innerText || node.textContent)
+                                : node.nodeValue;
+                            if (value != null) values.push(String(value).trim());
+                            node = result.iterateNext();
+                        }}
+                        return values;
+                    }}
+                }}
+            }} catch (e) {{
+                return [];
+            }}
+        }})()"#,
+        serde_json::to_string(expression).unwrap_or_default()
+    )
+}
+
+fn split_attribute(spec: &str) -> (&str, Option<&str>) {
+    match spec.rsplit_once('@') {
+        Some((selector, attribute))
+            if !selector.trim().is_empty()
+                && !selector.ends_with(['/', '['])
+                && !attribute.is_empty()
+                && attribute.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':')) =>
+        {
+            (selector.trim(), Some(attribute))
+        }
+        _ => (spec.trim(), None),
+    }
+}
+
+fn should_block(resource_type: &ResourceType, blocked_types: &[String]) -> bool {
+    let kind = resource_type.as_ref().to_ascii_lowercase();
+    blocked_types.contains(&kind)
+}
+
+fn crawl_key(url: &str) -> String {
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return url.split('#').next().unwrap_or(url).trim_end_matches('/').to_string();
+    };
+    let mut key = format!("{}{}", parsed.origin().ascii_serialization(), parsed.path().trim_end_matches('/'));
+    if let Some(query) = parsed.query() {
+        key.push('?');
+        key.push_str(query);
+    }
+    key
+}
+
+fn push_title(history: &mut Vec<String>, title: String) {
+    if history.last() != Some(&title) {
+        history.push(title);
+    }
+}
+
+fn content_hash(text: &str, html: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    for source in std::iter::once(text).chain(html) {
+        let normalized = source.split_whitespace().collect::<Vec<_>>().join(" ");
+        hasher.update(normalized.as_bytes());
+        hasher.update([0u8]);
+    }
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn detect_script(text: &str) -> Option<String> {
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        let script = match c as u32 {
+            0x0041..=0x024F | 0x1E00..=0x1EFF => "Latin",
+            0x0370..=0x03FF => "Greek",
+            0x0400..=0x052F => "Cyrillic",
+            0x0590..=0x05FF => "Hebrew",
+            0x0600..=0x06FF | 0x0750..=0x077F | 0x08A0..=0x08FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => "Arabic",
+            0x0900..=0x097F => "Devanagari",
+            0x0E00..=0x0E7F => "Thai",
+            0x1100..=0x11FF | 0xAC00..=0xD7AF => "Hangul",
+            0x3040..=0x30FF => "Kana",
+            0x4E00..=0x9FFF | 0x3400..=0x4DBF => "CJK",
+            _ => "Other",
+        };
+        *counts.entry(script).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .filter(|(script, _)| *script != "Other")
+        .max_by_key(|(_, count)| *count)
+        .map(|(script, _)| script.to_string())
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF)
+}
+
+fn unicode_block(c: char) -> &'static str {
+    match c as u32 {
+        0x0000..=0x007F => "Basic Latin",
+        0x0080..=0x00FF => "Latin-1 Supplement",
+        0x0100..=0x024F | 0x1E00..=0x1EFF => "Latin Extended",
+        0x0370..=0x03FF => "Greek and Coptic",
+        0x0400..=0x052F => "Cyrillic",
+        0x0590..=0x05FF => "Hebrew",
+        0x0600..=0x06FF | 0x0750..=0x077F => "Arabic",
+        0x0900..=0x097F => "Devanagari",
+        0x0E00..=0x0E7F => "Thai",
+        0x2000..=0x206F => "General Punctuation",
+        0x20A0..=0x20CF => "Currency Symbols",
+        0x3040..=0x309F => "Hiragana",
+        0x30A0..=0x30FF => "Katakana",
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF => "CJK Unified Ideographs",
+        0xAC00..=0xD7AF => "Hangul Syllables",
+        _ if is_emoji(c) => "Emoji",
+        _ => "Other",
+    }
+}
+
+fn compute_text_stats(text: &str) -> TextStats {
+    let mut blocks: HashMap<&'static str, usize> = HashMap::new();
+    let (mut char_count, mut emoji_count, mut letters, mut uppercase, mut digits) = (0, 0, 0, 0, 0);
+    for c in text.chars() {
+        char_count += 1;
+        if c.is_whitespace() {
+            continue;
+        }
+        if is_emoji(c) {
+            emoji_count += 1;
+        }
+        if c.is_alphabetic() {
+            letters += 1;
+            if c.is_uppercase() {
+                uppercase += 1;
+            }
+        }
+        if c.is_numeric() {
+            digits += 1;
+        }
+        *blocks.entry(unicode_block(c)).or_default() += 1;
+    }
+    let ratio = |part: usize, whole: usize| if whole == 0 { 0.0 } else { part as f64 / whole as f64 };
+    TextStats {
+        char_count,
+        emoji_count,
+        uppercase_ratio: ratio(uppercase, letters),
+        digit_ratio: ratio(digits, char_count),
+        dominant_block: blocks
+            .into_iter()
+            .filter(|(block, _)| *block != "Other")
+            .max_by_key(|(_, count)| *count)
+            .map(|(block, _)| block.to_string()),
+    }
+}
+
+async fn login_on_page(page: Page, credentials: LoginCredentials) -> PlatformLoginResult {
+    let target_url = credentials.login_url.clone().unwrap_or_else(|| {
+        get_platform_config(credentials.platform.as_deref().unwrap_or("generic"))
+            .login_url
+            .to_string()
+    });
+
+    let result = match auto_login(&page, &credentials, &target_url).await {
+        Ok((success, platform, requires_2fa, login_frame)) => PlatformLoginResult {
+            platform,
+            success,
+            requires_2fa,
+            error: None,
+            cookies: get_cookies(&page).await.unwrap_or_default(),
+            login_frame,
+        },
+        Err(e) => PlatformLoginResult {
+            platform: credentials.platform.clone(),
+            success: false,
+            requires_2fa: None,
+            error: Some(e.to_string()),
+            cookies: Vec::new(),
+            login_frame: None,
+        },
+    };
+    let _ = page.close().await;
+    result
+}
+
+pub async fn do_multi_login(req: &MultiLoginRequest) -> Result<MultiLoginResponse, ScrapeError> {
     let scraper = Scraper::new(true).await?;
-    scraper.scrape(url, login).await
-}
\ No newline at end of file
+
+    let mut logins = Vec::new();
+    for credentials in &req.logins {
+        let page = scraper.new_page().await?;
+        logins.push(login_on_page(page, credentials.clone()));
+    }
+    let results = join_all(logins).await;
+
+    let mut cookies: Vec<CookieData> = Vec::new();
+    for cookie in results.iter().flat_map(|r| r.cookies.iter()) {
+        let seen = cookies
+            .iter()
+            .any(|c| c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path);
+        if !seen {
+            cookies.push(cookie.clone());
+        }
+    }
+
+    Ok(MultiLoginResponse { results, cookies })
+}
+
+pub async fn do_scrape(req: &ScrapeRequest) -> Result<ScrapedData, ScrapeError> {
+    if let Some(session_id) = &req.session_id {
+        return scrape_in_session(session_id, req).await;
+    }
+    let (scraper, proxy) = acquire_scraper(req).await?;
+    let mut data = scraper.scrape(req).await?;
+    data.proxy_used = proxy;
+    Ok(data)
+}
+
+pub async fn do_render(req: &ScrapeRequest) -> Result<String, ScrapeError> {
+    if req.session_id.is_some() {
+        return Err(ScrapeError::InvalidRequest("Sessions are not supported for rendering".to_string()));
+    }
+    let (scraper, _) = acquire_scraper(req).await?;
+    scraper.render(req).await
+}
+
+async fn acquire_scraper(req: &ScrapeRequest) -> Result<(Scraper, Option<String>), ScrapeError> {
+    if let Some(ws_url) = &req.connect_url {
+        return Ok((Scraper::connect(ws_url).await?, None));
+    }
+    let options = LaunchOptions::from_request(req)?;
+    let scraper = if options.uses_defaults() {
+        Scraper::from_pool(pool::checkout().await?).await?
+    } else {
+        Scraper::with_options(&options).await?
+    };
+    let proxy = options.proxy.as_ref().or(env_proxy()).map(ProxyConfig::server);
+    Ok((scraper, proxy))
+}
+
+pub async fn do_crawl(
+    req: &ScrapeRequest,
+    options: &CrawlOptions,
+    on_result: impl FnMut(&str, Result<ScrapedData, ScrapeError>),
+) -> Result<(), ScrapeError> {
+    if req.session_id.is_some() {
+        return Err(ScrapeError::InvalidRequest("Sessions are not supported for crawling".to_string()));
+    }
+    let (scraper, _) = acquire_scraper(req).await?;
+    scraper.crawl(req, options, on_result).await
+}
+
+pub async fn do_scrape_batch(
+    req: &ScrapeRequest,
+    urls: &[String],
+    mut on_result: impl FnMut(&str, Result<ScrapedData, ScrapeError>),
+) -> Result<(), ScrapeError> {
+    let per_url = |url: &String| ScrapeRequest { url: url.clone(), urls: None, ..req.clone() };
+
+    if req.session_id.is_some() || req.proxy_pool.is_some() {
+        for url in urls {
+            on_result(url, do_scrape(&per_url(url)).await);
+        }
+        return Ok(());
+    }
+
+    let (scraper, proxy) = acquire_scraper(req).await?;
+    for url in urls {
+        emit("status", format!("Batch scrape {}", url));
+        let result = scraper.scrape(&per_url(url)).await.map(|mut data| {
+            data.proxy_used = proxy.clone();
+            data
+        });
+        on_result(url, result);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn month_date_patterns_match_written_dates() {
+        let patterns: Vec<Regex> = month_date_patterns()
+            .iter()
+            .map(|source| Regex::new(&format!("(?i){}", source)).unwrap())
+            .collect();
+        let text = "Published March 5, 2024 and updated 12 Sept. 2024.";
+        let found: Vec<&str> = patterns
+            .iter()
+            .flat_map(|pattern| pattern.find_iter(text).map(|m| m.as_str()))
+            .collect();
+        assert_eq!(found, vec!["March 5, 2024", "12 Sept. 2024"]);
+    }
+
+    #[test]
+    fn month_date_patterns_survive_json_injection() {
+        let json = serde_json::to_string(&month_date_patterns()).unwrap();
+        let decoded: Vec<String> = serde_json::from_str(&json).unwrap();
+        assert!(decoded[0].starts_with(r"\b(?:Jan"));
+        assert!(decoded[1].contains(r"\d{1,2}"));
+    }
+
+    #[test]
+    fn is_emoji_covers_pictographs_and_flags() {
+        assert!(is_emoji('😀'));
+        assert!(is_emoji('☀'));
+        assert!(is_emoji('🇺'));
+        assert!(!is_emoji('a'));
+        assert!(!is_emoji('é'));
+    }
+
+    #[test]
+    fn text_stats_counts_emoji_case_and_digits() {
+        let stats = compute_text_stats("Hi 👋 AB12");
+        assert_eq!(stats.char_count, 9);
+        assert_eq!(stats.emoji_count, 1);
+        assert_eq!(stats.uppercase_ratio, 0.75);
+        assert!((stats.digit_ratio - 2.0 / 9.0).abs() < 1e-9);
+        assert_eq!(stats.dominant_block.as_deref(), Some("Basic Latin"));
+    }
+
+    #[test]
+    fn text_stats_handles_empty_text() {
+        let stats = compute_text_stats("");
+        assert_eq!(stats.char_count, 0);
+        assert_eq!(stats.uppercase_ratio, 0.0);
+        assert_eq!(stats.digit_ratio, 0.0);
+        assert_eq!(stats.dominant_block, None);
+    }
+
+    #[test]
+    fn split_attribute_separates_trailing_attribute() {
+        assert_eq!(split_attribute("a.product @href"), ("a.product", Some("href")));
+        assert_eq!(split_attribute("img@data-src"), ("img", Some("data-src")));
+        assert_eq!(split_attribute("  h1.title "), ("h1.title", None));
+    }
+
+    #[test]
+    fn split_attribute_leaves_xpath_attribute_steps_alone() {
+        assert_eq!(split_attribute("//td/@href"), ("//td/@href", None));
+        assert_eq!(split_attribute("//a[@class='next']"), ("//a[@class='next']", None));
+        assert_eq!(split_attribute("//tr[@id='price']/td[2]"), ("//tr[@id='price']/td[2]", None));
+        assert_eq!(split_attribute(r#"a[href^="mailto:sales@example.com"]"#).1, None);
+    }
+
+    #[test]
+    fn extraction_scripts_quote_their_input() {
+        let script = xpath_script(r#"//td[contains(., "Price")]/following-sibling::td"#);
+        assert!(script.contains(
+            r#"document.evaluate("//td[contains(., \"Price\")]/following-sibling::td", document"#
+        ));
+
+        let script = css_script("a[title='it\\'s']@href");
+        assert!(script.contains(r#"const attribute = "href";"#));
+        assert!(script.contains(r#"querySelectorAll("a[title='it\\'s']")"#));
+
+        let script = css_script("p.summary");
+        assert!(script.contains("const attribute = null;"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Chrome binary"]
+    async fn xpath_fields_extract_node_sets_strings_and_numbers() {
+        let scraper = Scraper::new(true).await.unwrap();
+        scraper
+            .page()
+            .set_content(include_str!("../tests/fixtures/xpath_table.html"))
+            .await
+            .unwrap();
+
+        let fields = HashMap::from([
+            ("products".to_string(), "xpath: //table[@id='prices']/tbody/tr/td[1]".to_string()),
+            ("gadget".to_string(), "xpath: string(//tr[td[1]='Gadget']/td[2])".to_string()),
+            ("rows".to_string(), "xpath: count(//table[@id='prices']/tbody/tr)".to_string()),
+            ("total".to_string(), "xpath: sum(//table[@id='prices']/tbody/tr/td[2])".to_string()),
+        ]);
+        let mut warnings = Vec::new();
+        let values = scraper.extract_fields(&fields, &mut warnings).await;
+
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(values["products"], ["Widget", "Gadget", "Gizmo"]);
+        assert_eq!(values["gadget"], ["5.50"]);
+        assert_eq!(values["rows"], ["3"]);
+        assert_eq!(values["total"], ["145.49"]);
+    }
+
+    #[test]
+    fn blocking_images_fails_only_image_requests() {
+        let blocked = vec!["image".to_string()];
+        assert!(should_block(&ResourceType::Image, &blocked));
+        assert!(!should_block(&ResourceType::Document, &blocked));
+        assert!(!should_block(&ResourceType::Stylesheet, &blocked));
+        assert!(!should_block(&ResourceType::Script, &blocked));
+        assert!(!should_block(&ResourceType::Image, &[]));
+    }
+
+    #[test]
+    fn blocked_types_match_cdp_resource_names() {
+        let blocked = vec!["xhr".to_string(), "texttrack".to_string(), "font".to_string()];
+        assert!(should_block(&ResourceType::Xhr, &blocked));
+        assert!(should_block(&ResourceType::TextTrack, &blocked));
+        assert!(should_block(&ResourceType::Font, &blocked));
+        assert!(!should_block(&ResourceType::Media, &blocked));
+    }
+
+    #[test]
+    fn crawl_key_lowercases_only_scheme_and_host() {
+        assert_eq!(crawl_key("HTTPS://Example.COM/Docs/Page/#intro"), "https://example.com/Docs/Page");
+        assert_eq!(crawl_key("https://example.com:443/a?Q=1#x"), "https://example.com/a?Q=1");
+        assert_eq!(crawl_key("https://example.com/"), crawl_key("https://EXAMPLE.com"));
+        assert_ne!(crawl_key("https://example.com/Docs"), crawl_key("https://example.com/docs"));
+        assert_ne!(crawl_key("https://example.com/a?id=1"), crawl_key("https://example.com/a?id=2"));
+        assert_ne!(crawl_key("http://example.com/a"), crawl_key("http://example.com:8080/a"));
+    }
+
+    #[test]
+    fn block_resources_are_normalized_when_the_request_is_parsed() {
+        let req: ScrapeRequest = serde_json::from_value(serde_json::json!({
+            "url": "https://example.com",
+            "block_resources": ["Image", " FONT ", "xhr"]
+        }))
+        .unwrap();
+        let blocked = req.block_resources.unwrap();
+        assert_eq!(blocked, ["image", "font", "xhr"]);
+        assert!(should_block(&ResourceType::Image, &blocked));
+        assert!(should_block(&ResourceType::Font, &blocked));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a Chrome binary"]
+    async fn blocking_images_keeps_image_requests_off_the_network() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let image_hits = Arc::new(AtomicUsize::new(0));
+        let hits = image_hits.clone();
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = [0u8; 4096];
+                let read = socket.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]);
+                let (content_type, body) = if request.starts_with("GET /pixel.png") {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    ("image/png", "")
+                } else {
+                    ("text/html", "<html><body><h1>Gallery</h1><img src=\"/pixel.png\"></body></html>")
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    content_type,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let req: ScrapeRequest = serde_json::from_value(serde_json::json!({
+            "url": format!("http://127.0.0.1:{}/", port),
+            "block_resources": ["Image"]
+        }))
+        .unwrap();
+        let scraper = Scraper::new(true).await.unwrap();
+        scraper.scrape(&req).await.unwrap();
+
+        assert_eq!(image_hits.load(Ordering::SeqCst), 0);
+    }
+}
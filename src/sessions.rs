@@ -0,0 +1,79 @@
+use crate::cdp::RawCommand;
+use crate::errors::ScrapeError;
+use crate::model::{LoginCredentials, ScrapeRequest, ScrapedData};
+use crate::scraper::{LaunchOptions, Scraper};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::Mutex;
+use tracing::info;
+
+pub struct Session {
+    scraper: Scraper,
+    // Kept in process memory only so an expired session can log in again.
+    credentials: Option<LoginCredentials>,
+}
+
+type SessionMap = Mutex<HashMap<String, Arc<Mutex<Session>>>>;
+
+fn sessions() -> &'static SessionMap {
+    static SESSIONS: OnceLock<SessionMap> = OnceLock::new();
+    SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+async fn get_or_create(
+    session_id: &str,
+    req: &ScrapeRequest,
+) -> Result<(Arc<Mutex<Session>>, bool), ScrapeError> {
+    let mut map = sessions().lock().await;
+    if let Some(session) = map.get(session_id) {
+        return Ok((session.clone(), false));
+    }
+    let scraper = Scraper::with_options(&LaunchOptions::from_request(req)?).await?;
+    let session = Arc::new(Mutex::new(Session { scraper, credentials: None }));
+    map.insert(session_id.to_string(), session.clone());
+    Ok((session, true))
+}
+
+pub async fn execute_in_session(
+    session_id: &str,
+    command: RawCommand,
+) -> Result<serde_json::Value, ScrapeError> {
+    let session = sessions()
+        .lock()
+        .await
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| ScrapeError::InvalidRequest(format!("Unknown session: {}", session_id)))?;
+    let session = session.lock().await;
+    session.scraper.execute_raw(command).await
+}
+
+pub async fn close_session(session_id: &str) -> bool {
+    sessions().lock().await.remove(session_id).is_some()
+}
+
+pub async fn scrape_in_session(session_id: &str, req: &ScrapeRequest) -> Result<ScrapedData, ScrapeError> {
+    let (session, created) = get_or_create(session_id, req).await?;
+    let mut session = session.lock().await;
+
+    if let Some(credentials) = &req.login {
+        session.credentials = Some(credentials.clone());
+        return session.scraper.scrape(req).await;
+    }
+
+    let stored = match (&session.credentials, created) {
+        (Some(credentials), false) => credentials.clone(),
+        _ => return session.scraper.scrape(req).await,
+    };
+
+    if session.scraper.is_authenticated(&req.url, &stored).await {
+        return session.scraper.scrape(req).await;
+    }
+
+    info!("Session {} appears logged out, refreshing login", session_id);
+    let mut refresh_req = req.clone();
+    refresh_req.login = Some(stored);
+    let mut data = session.scraper.scrape(&refresh_req).await?;
+    data.session_refreshed = data.login_success == Some(true);
+    Ok(data)
+}
@@ -0,0 +1,69 @@
+use chromiumoxide::browser::Browser;
+use chromiumoxide::cdp::browser_protocol::browser::CloseParams;
+use futures::StreamExt;
+use futures::future::join_all;
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+fn live_browsers() -> &'static Mutex<HashSet<String>> {
+    static LIVE: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    LIVE.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+pub fn shutdown_timeout() -> Duration {
+    static TIMEOUT: OnceLock<Duration> = OnceLock::new();
+    *TIMEOUT.get_or_init(|| {
+        let secs = std::env::var("SHUTDOWN_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10);
+        Duration::from_secs(secs)
+    })
+}
+
+pub fn register(browser: &Browser) {
+    if let Ok(mut live) = live_browsers().lock() {
+        live.insert(browser.websocket_address().clone());
+    }
+}
+
+pub fn unregister(ws_url: &str) {
+    if let Ok(mut live) = live_browsers().lock() {
+        live.remove(ws_url);
+    }
+}
+
+async fn close(ws_url: String) {
+    match Browser::connect(ws_url.as_str()).await {
+        Ok((browser, mut handler)) => {
+            let driver = tokio::spawn(async move {
+                while handler.next().await.is_some() {}
+            });
+            if let Err(e) = browser.execute(CloseParams::default()).await {
+                warn!("Failed to close browser {}: {}", ws_url, e);
+            }
+            driver.abort();
+        }
+        Err(e) => warn!("Failed to reach browser {} for shutdown: {}", ws_url, e),
+    }
+}
+
+pub async fn close_all() {
+    let targets: Vec<String> = live_browsers()
+        .lock()
+        .map(|mut live| live.drain().collect())
+        .unwrap_or_default();
+    if targets.is_empty() {
+        return;
+    }
+
+    info!("Closing {} browsers before exit", targets.len());
+    if tokio::time::timeout(shutdown_timeout(), join_all(targets.into_iter().map(close)))
+        .await
+        .is_err()
+    {
+        warn!("Timed out closing browsers after {:?}", shutdown_timeout());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::errors::ScrapeError;
+use flate2::read::GzDecoder;
+use regex::Regex;
+use std::collections::HashSet;
+use std::io::Read;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::warn;
+
+const MAX_SITEMAP_BYTES: usize = 20 * 1024 * 1024;
+const MAX_SITEMAPS: usize = 50;
+
+fn loc_pattern() -> &'static Regex {
+    static LOC: OnceLock<Regex> = OnceLock::new();
+    LOC.get_or_init(|| Regex::new(r"(?is)<loc>\s*(?:<!\[CDATA\[)?(.*?)(?:\]\]>)?\s*</loc>").unwrap())
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+pub fn sitemap_url(setting: &str, page_url: &str) -> Result<String, ScrapeError> {
+    let setting = setting.trim();
+    if !setting.is_empty() && setting != "auto" {
+        return Ok(setting.to_string());
+    }
+    let mut origin = reqwest::Url::parse(page_url)
+        .map_err(|_| ScrapeError::InvalidRequest(format!("Cannot derive sitemap from {}", page_url)))?;
+    origin.set_path("/sitemap.xml");
+    origin.set_query(None);
+    origin.set_fragment(None);
+    Ok(origin.to_string())
+}
+
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<String, ScrapeError> {
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| ScrapeError::ContentExtraction(format!("sitemap download: {}", e)))?;
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| ScrapeError::ContentExtraction(format!("sitemap download: {}", e)))?
+    {
+        if bytes.len() + chunk.len() > MAX_SITEMAP_BYTES {
+            return Err(ScrapeError::ContentExtraction("sitemap exceeds size limit".to_string()));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut xml = String::new();
+        GzDecoder::new(bytes.as_slice())
+            .take(MAX_SITEMAP_BYTES as u64)
+            .read_to_string(&mut xml)
+            .map_err(|e| ScrapeError::ContentExtraction(format!("sitemap gzip: {}", e)))?;
+        return Ok(xml);
+    }
+    String::from_utf8(bytes).map_err(|e| ScrapeError::ContentExtraction(format!("sitemap encoding: {}", e)))
+}
+
+pub async fn collect_urls(url: &str, limit: usize) -> Result<Vec<String>, ScrapeError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| ScrapeError::ContentExtraction(format!("sitemap client: {}", e)))?;
+
+    let mut pending = vec![url.to_string()];
+    let mut seen_sitemaps = HashSet::new();
+    let mut seen_urls = HashSet::new();
+    let mut urls = Vec::new();
+    while let Some(sitemap) = pending.pop() {
+        if urls.len() >= limit || seen_sitemaps.len() >= MAX_SITEMAPS {
+            break;
+        }
+        if !seen_sitemaps.insert(sitemap.clone()) {
+            continue;
+        }
+        let xml = match fetch(&client, &sitemap).await {
+            Ok(xml) => xml,
+            Err(e) if sitemap != url => {
+                warn!("Skipping child sitemap {}: {}", sitemap, e);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        let locs = loc_pattern()
+            .captures_iter(&xml)
+            .map(|c| unescape(c[1].trim()))
+            .filter(|loc| !loc.is_empty());
+        if xml.contains("<sitemapindex") {
+            pending.extend(locs.collect::<Vec<_>>().into_iter().rev());
+            continue;
+        }
+        for loc in locs {
+            if urls.len() >= limit {
+                break;
+            }
+            if seen_urls.insert(loc.clone()) {
+                urls.push(loc);
+            }
+        }
+    }
+
+    if urls.is_empty() {
+        return Err(ScrapeError::ContentExtraction(format!("No urls found in sitemap {}", url)));
+    }
+    Ok(urls)
+}
@@ -0,0 +1,70 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const MAX_ATTEMPTS: u32 = 3;
+
+fn webhook_secret() -> Option<&'static str> {
+    static SECRET: OnceLock<Option<String>> = OnceLock::new();
+    SECRET
+        .get_or_init(|| std::env::var("WEBHOOK_SECRET").ok().filter(|v| !v.is_empty()))
+        .as_deref()
+}
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+pub fn is_valid_callback(url: &str) -> bool {
+    reqwest::Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https") && u.host().is_some())
+}
+
+pub async fn deliver(url: &str, payload: &serde_json::Value) -> bool {
+    let body = match serde_json::to_vec(payload) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to serialize callback payload: {}", e);
+            return false;
+        }
+    };
+    let signature = webhook_secret().map(|secret| sign(secret, &body));
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = client()
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header("X-Scraper-Signature", signature);
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                info!("Delivered callback to {}", url);
+                return true;
+            }
+            Ok(response) => warn!("Callback to {} returned {} (attempt {})", url, response.status(), attempt),
+            Err(e) => warn!("Callback to {} failed: {} (attempt {})", url, e, attempt),
+        }
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+        }
+    }
+    false
+}